@@ -0,0 +1,68 @@
+use std::fmt::Write;
+use std::path::Path;
+
+#[allow(dead_code)]
+#[path = "src/spec.rs"]
+mod spec;
+
+use spec::{Direction, Edition, Spec};
+
+fn main() {
+    println!("cargo:rerun-if-changed=spec/aojia.json");
+    println!("cargo:rerun-if-changed=src/spec.rs");
+
+    let spec = Spec::builtin();
+    let mut out = String::from("bindings! {\n");
+    for m in &spec.methods {
+        writeln!(out, "    /// {}", m.doc).unwrap();
+        let defaults: Vec<String> = m
+            .params
+            .iter()
+            .filter_map(|p| p.default.as_ref().map(|d| format!("`{} = {}`", p.name, d)))
+            .collect();
+        if !defaults.is_empty() {
+            writeln!(out, "    ///\n    /// 默认值：{}", defaults.join("，")).unwrap();
+        }
+        let outs: Vec<&str> = m
+            .params
+            .iter()
+            .filter(|p| p.dir == Direction::Out)
+            .map(|p| p.name.as_str())
+            .collect();
+        if !outs.is_empty() {
+            writeln!(out, "    ///\n    /// 传址参数：{}", outs.join("、")).unwrap();
+        }
+        if !m.failure.is_empty() {
+            let codes: Vec<String> = m.failure.iter().map(i64::to_string).collect();
+            writeln!(
+                out,
+                "    ///\n    /// 返回 {} 时视为失败，返回 `AoJiaError::Plugin`。",
+                codes.join("、")
+            )
+            .unwrap();
+        }
+        if m.edition == Edition::Paid {
+            writeln!(
+                out,
+                "    ///\n    /// 收费版插件提供，免费版中调用时返回 COM 错误。"
+            )
+            .unwrap();
+        }
+        if !m.failure.is_empty() {
+            let codes: Vec<String> = m.failure.iter().map(i64::to_string).collect();
+            writeln!(out, "    #[failure({})]", codes.join(", ")).unwrap();
+        }
+        writeln!(
+            out,
+            "    fn {}({}) -> {};",
+            m.name,
+            m.rust_params(),
+            m.returns.rust_owned()
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(Path::new(&out_dir).join("bindings.rs"), out).unwrap();
+}
@@ -0,0 +1,137 @@
+//! 异步接口，需开启 `async` feature
+//!
+//! 调用仍在插件线程上同步执行，[`AsyncAoJia`] 只是不再阻塞调用方的线程。
+//! 超时依赖 tokio 的计时器，需在开启 `time` 的 tokio 运行时中使用。
+
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::AoJia;
+use crate::dispatch::{Arg, Call, Dispatch, Value};
+use crate::error::{AoJiaError, Result};
+use crate::remote::{AoJiaHandle, RPC_E_DISCONNECTED, Remote};
+
+/// 插件对象的异步版本，方法与 [`AoJia`] 同名，返回 future
+///
+/// 可克隆并跨线程传递，所有克隆共用同一个插件线程。
+/// future 在调用开始执行前被丢弃时，该调用不会再执行。
+#[derive(Debug, Clone)]
+pub struct AsyncAoJia {
+    remote: Remote,
+    timeout: Option<Duration>,
+}
+
+impl AsyncAoJia {
+    pub fn new(remote: Remote) -> Self {
+        Self {
+            remote,
+            timeout: None,
+        }
+    }
+
+    /// 创建插件线程，见 [`Remote::spawn`]
+    pub fn spawn<D, F>(factory: F) -> Result<Self>
+    where
+        D: Dispatch + 'static,
+        F: FnOnce() -> Result<D> + Send + 'static,
+    {
+        Remote::spawn(factory).map(Self::new)
+    }
+
+    /// 在插件线程上加载插件
+    #[cfg(windows)]
+    pub fn spawn_with_path(a_regj_path: String, ao_jia_path: String) -> Result<Self> {
+        Self::spawn(move || crate::ComBackend::new_with_path(a_regj_path, ao_jia_path))
+    }
+
+    /// 返回使用 `timeout` 作为超时的副本，超时后返回 `AoJiaError::Timeout`
+    ///
+    /// 已开始执行的调用无法中断，超时只是不再等待其结果。
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            remote: self.remote.clone(),
+            timeout: Some(timeout),
+        }
+    }
+
+    /// 返回不限时的副本
+    pub fn without_timeout(&self) -> Self {
+        Self {
+            remote: self.remote.clone(),
+            timeout: None,
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// 对应的同步句柄，与本对象共用插件线程
+    pub fn blocking(&self) -> AoJiaHandle {
+        AoJia::with_backend(self.remote.clone())
+    }
+
+    /// 在插件线程上执行 `f`，用于组合多个调用或使用配套方法
+    pub async fn run<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&AoJia<&dyn Dispatch>) -> Result<R> + Send + 'static,
+    {
+        self.execute(Call::new("run", &[]), move |backend| {
+            f(&AoJia::with_backend(backend))
+        })
+        .await
+    }
+
+    pub(crate) async fn invoke(&self, name: &str, args: Vec<Arg>) -> Result<(Value, Vec<Arg>)> {
+        let owned_name = name.to_string();
+        let call = Call::new(name, &args);
+        let mut args = args;
+        self.execute(call, move |backend| {
+            let ret = backend.invoke(&owned_name, &mut args)?;
+            Ok((ret, args))
+        })
+        .await
+    }
+
+    async fn execute<R, F>(&self, call: Call, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&dyn Dispatch) -> Result<R> + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        let submitted = self.remote.submit(Box::new(move |backend| {
+            // 调用方已放弃等待时跳过
+            if !reply.is_closed() {
+                let _ = reply.send(f(backend));
+            }
+        }));
+        let disconnected = |call, message| AoJiaError::com(call, RPC_E_DISCONNECTED, message);
+        if !submitted {
+            return Err(disconnected(call, "插件线程已退出"));
+        }
+
+        let received = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, result).await {
+                Ok(received) => received,
+                Err(_) => return Err(AoJiaError::Timeout { call, timeout }),
+            },
+            None => result.await,
+        };
+        received.unwrap_or_else(|_| Err(disconnected(call, "插件线程处理调用时崩溃")))
+    }
+}
+
+impl From<Remote> for AsyncAoJia {
+    fn from(remote: Remote) -> Self {
+        Self::new(remote)
+    }
+}
+
+impl AoJia<Remote> {
+    /// 对应的异步句柄，与本对象共用插件线程
+    pub fn to_async(&self) -> AsyncAoJia {
+        AsyncAoJia::new(self.backend().clone())
+    }
+}
@@ -0,0 +1,474 @@
+//! 后台绑定模式
+//!
+//! `KQHouTai` 的 `Screen`、`Keyboard`、`Mouse` 及 `Flag` 参数均为字符串，
+//! 这里以枚举及标志位表示，并在调用插件前检查组合是否有效。
+//! [`AoJia::bind`] 返回的 [`BindingGuard`] 在离开作用域时自动调用 `GBHouTai`。
+
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+use std::str::FromStr;
+
+use crate::AoJia;
+use crate::dispatch::{Call, Dispatch, IntoArg};
+use crate::error::{AoJiaError, ParseError, Result};
+use crate::ocr::{OcrRequest, OcrResult};
+use crate::pic::PicSearch;
+use crate::typed::{PicMatch, Point, Size};
+use crate::window::Hwnd;
+
+// 生成与插件字符串一一对应的模式枚举
+macro_rules! modes {
+    ($(#[doc = $doc:literal])* $name:ident, $what:literal {
+        $($(#[doc = $vdoc:literal])* $variant:ident = $s:literal,)*
+    }) => {
+        $(#[doc = $doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub enum $name {
+            #[default]
+            $($(#[doc = $vdoc])* $variant,)*
+        }
+
+        impl $name {
+            /// 所有取值
+            pub const ALL: &[$name] = &[$($name::$variant),*];
+
+            /// 插件使用的字符串
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => $s,)*
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                let mode = s.trim();
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|m| m.as_str().eq_ignore_ascii_case(mode))
+                    .ok_or_else(|| {
+                        let valid: Vec<&str> = Self::ALL.iter().map(|m| m.as_str()).collect();
+                        ParseError::new($what, s, format!("应为 {} 之一", valid.join("、")))
+                    })
+            }
+        }
+    };
+}
+
+modes! {
+    /// `KQHouTai` 的 `Screen` 参数
+    ScreenMode, "屏幕模式" {
+        /// 前台截图
+        Normal = "normal",
+        /// GDI 后台截图
+        Gdi = "gdi",
+        /// GDI 后台截图，兼容部分 GDI 模式无法截图的窗口
+        Gdi2 = "gdi2",
+        /// DirectX 后台截图
+        Dx = "dx",
+        /// DirectX 后台截图，兼容部分 dx 模式无法截图的窗口
+        Dx2 = "dx2",
+    }
+}
+
+modes! {
+    /// `KQHouTai` 的 `Keyboard` 参数
+    KeyboardMode, "键盘模式" {
+        /// 前台键盘
+        Normal = "normal",
+        /// 以窗口消息模拟键盘
+        Windows = "windows",
+        /// DirectX 后台键盘
+        Dx = "dx",
+    }
+}
+
+modes! {
+    /// `KQHouTai` 的 `Mouse` 参数
+    MouseMode, "鼠标模式" {
+        /// 前台鼠标
+        Normal = "normal",
+        /// 以窗口消息模拟鼠标
+        Windows = "windows",
+        /// 以窗口消息模拟鼠标，同时发送激活消息
+        Windows2 = "windows2",
+        /// DirectX 后台鼠标
+        Dx = "dx",
+        /// DirectX 后台鼠标，锁定鼠标位置
+        Dx2 = "dx2",
+    }
+}
+
+impl ScreenMode {
+    pub fn is_dx(self) -> bool {
+        matches!(self, ScreenMode::Dx | ScreenMode::Dx2)
+    }
+}
+
+impl KeyboardMode {
+    pub fn is_dx(self) -> bool {
+        self == KeyboardMode::Dx
+    }
+}
+
+impl MouseMode {
+    pub fn is_dx(self) -> bool {
+        matches!(self, MouseMode::Dx | MouseMode::Dx2)
+    }
+}
+
+/// `KQHouTai` 的 `Flag` 参数，多个标志以 `|` 相连
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BindFlags {
+    bits: u32,
+}
+
+impl BindFlags {
+    /// 以 API 方式保持窗口激活
+    pub const ACTIVE_API: BindFlags = BindFlags::from_bits(1);
+    /// 以消息方式保持窗口激活
+    pub const ACTIVE_MESSAGE: BindFlags = BindFlags::from_bits(1 << 1);
+    /// 隐藏注入的模块
+    pub const HIDE_DLL: BindFlags = BindFlags::from_bits(1 << 2);
+    /// 截图时绕过窗口的截图保护
+    pub const GRAPHIC_PROTECT: BindFlags = BindFlags::from_bits(1 << 3);
+    /// 支持输入法输入
+    pub const INPUT_IME: BindFlags = BindFlags::from_bits(1 << 4);
+    /// 禁止窗口移动
+    pub const DISABLE_WINDOW_POSITION: BindFlags = BindFlags::from_bits(1 << 5);
+    /// 禁止窗口改变大小
+    pub const DISABLE_WINDOW_SIZE: BindFlags = BindFlags::from_bits(1 << 6);
+
+    // 标志及其在插件中的字符串，按此顺序输出
+    const NAMES: &[(BindFlags, &'static str)] = &[
+        (Self::ACTIVE_API, "dx.public.active.api"),
+        (Self::ACTIVE_MESSAGE, "dx.public.active.message"),
+        (Self::HIDE_DLL, "dx.public.hide.dll"),
+        (Self::GRAPHIC_PROTECT, "dx.public.graphic.protect"),
+        (Self::INPUT_IME, "dx.public.input.ime"),
+        (
+            Self::DISABLE_WINDOW_POSITION,
+            "dx.public.disable.window.position",
+        ),
+        (Self::DISABLE_WINDOW_SIZE, "dx.public.disable.window.size"),
+    ];
+
+    const fn from_bits(bits: u32) -> Self {
+        Self { bits }
+    }
+
+    pub const fn empty() -> Self {
+        Self::from_bits(0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn contains(self, other: BindFlags) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    pub fn intersects(self, other: BindFlags) -> bool {
+        self.bits & other.bits != 0
+    }
+
+    /// 各标志在插件中的字符串
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .iter()
+            .filter(move |(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+    }
+}
+
+impl BitOr for BindFlags {
+    type Output = BindFlags;
+
+    fn bitor(self, rhs: BindFlags) -> BindFlags {
+        BindFlags::from_bits(self.bits | rhs.bits)
+    }
+}
+
+impl BitOrAssign for BindFlags {
+    fn bitor_assign(&mut self, rhs: BindFlags) {
+        self.bits |= rhs.bits;
+    }
+}
+
+impl fmt::Display for BindFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.names().collect::<Vec<_>>().join("|"))
+    }
+}
+
+impl FromStr for BindFlags {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.split('|')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(BindFlags::empty(), |flags, name| {
+                Self::NAMES
+                    .iter()
+                    .find(|(_, n)| n.eq_ignore_ascii_case(name))
+                    .map(|(flag, _)| flags | *flag)
+                    .ok_or_else(|| ParseError::new("绑定标志", s, format!("未知的标志 {name:?}")))
+            })
+    }
+}
+
+/// `KQHouTai` 的绑定模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BindModes {
+    pub screen: ScreenMode,
+    pub keyboard: KeyboardMode,
+    pub mouse: MouseMode,
+    pub flags: BindFlags,
+    /// 插件文档中的 `Type` 参数
+    pub ty: i32,
+}
+
+impl BindModes {
+    pub fn new(screen: ScreenMode, keyboard: KeyboardMode, mouse: MouseMode) -> Self {
+        Self {
+            screen,
+            keyboard,
+            mouse,
+            ..Self::default()
+        }
+    }
+
+    pub fn flags(mut self, flags: BindFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn ty(mut self, ty: i32) -> Self {
+        self.ty = ty;
+        self
+    }
+
+    /// 由插件使用的字符串解析，并检查组合是否有效
+    pub fn parse(
+        screen: &str,
+        keyboard: &str,
+        mouse: &str,
+        flag: &str,
+    ) -> std::result::Result<Self, ParseError> {
+        let modes =
+            Self::new(screen.parse()?, keyboard.parse()?, mouse.parse()?).flags(flag.parse()?);
+        modes.validate()?;
+        Ok(modes)
+    }
+
+    /// 检查组合是否有效
+    ///
+    /// 所有标志都作用于 dx 模式：`INPUT_IME` 需要 dx 键盘，
+    /// `ACTIVE_API`、`ACTIVE_MESSAGE` 需要 dx 键盘或鼠标，
+    /// `GRAPHIC_PROTECT` 需要 dx 截图，其余标志需要至少一项为 dx 模式。
+    pub fn validate(&self) -> std::result::Result<(), ParseError> {
+        let err = |reason: &str| ParseError::new("后台绑定模式", &self.to_string(), reason);
+        let input_dx = self.keyboard.is_dx() || self.mouse.is_dx();
+        if self.flags.contains(BindFlags::INPUT_IME) && !self.keyboard.is_dx() {
+            return Err(err("dx.public.input.ime 需要 dx 键盘模式"));
+        }
+        if self
+            .flags
+            .intersects(BindFlags::ACTIVE_API | BindFlags::ACTIVE_MESSAGE)
+            && !input_dx
+        {
+            return Err(err("dx.public.active.* 需要 dx 键盘或鼠标模式"));
+        }
+        if self.flags.contains(BindFlags::GRAPHIC_PROTECT) && !self.screen.is_dx() {
+            return Err(err("dx.public.graphic.protect 需要 dx 屏幕模式"));
+        }
+        if !self.flags.is_empty() && !input_dx && !self.screen.is_dx() {
+            return Err(err("绑定标志需要至少一项为 dx 模式"));
+        }
+        Ok(())
+    }
+
+    fn call(&self, hwnd: Hwnd) -> Call {
+        Call::new(
+            "KQHouTai",
+            &[
+                hwnd.into_arg(),
+                self.screen.as_str().into_arg(),
+                self.keyboard.as_str().into_arg(),
+                self.mouse.as_str().into_arg(),
+                self.flags.to_string().as_str().into_arg(),
+                self.ty.into_arg(),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for BindModes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Screen={} Keyboard={} Mouse={} Flag={}",
+            self.screen, self.keyboard, self.mouse, self.flags
+        )
+    }
+}
+
+impl<B: Dispatch> AoJia<B> {
+    /// `KQHouTai` 的配套方法，组合无效时不调用插件，返回 `AoJiaError::InvalidArgument`
+    pub fn kq_hou_tai(&self, hwnd: Hwnd, modes: &BindModes) -> Result<()> {
+        modes
+            .validate()
+            .map_err(|source| AoJiaError::InvalidArgument {
+                call: modes.call(hwnd),
+                source,
+            })?;
+        self.KQHouTai(
+            hwnd,
+            modes.screen.as_str(),
+            modes.keyboard.as_str(),
+            modes.mouse.as_str(),
+            &modes.flags.to_string(),
+            modes.ty,
+        )?;
+        Ok(())
+    }
+}
+
+/// 解除绑定失败时的处理函数
+pub type UnbindHook<'a> = Box<dyn FnMut(AoJiaError) + 'a>;
+
+/// 后台绑定期间的句柄，离开作用域时调用 `GBHouTai` 解除绑定
+///
+/// 析构时的错误交给 [`AoJia::bind`] 传入的处理函数；
+/// 需要就地处理错误时可调用 [`BindingGuard::unbind`]。
+pub struct BindingGuard<'a, B: Dispatch> {
+    aojia: &'a AoJia<B>,
+    hwnd: Hwnd,
+    modes: BindModes,
+    on_unbind_error: UnbindHook<'a>,
+    bound: bool,
+}
+
+impl<'a, B: Dispatch> BindingGuard<'a, B> {
+    pub fn hwnd(&self) -> Hwnd {
+        self.hwnd
+    }
+
+    pub fn modes(&self) -> &BindModes {
+        &self.modes
+    }
+
+    pub fn aojia(&self) -> &'a AoJia<B> {
+        self.aojia
+    }
+
+    /// 立即解除绑定并返回结果，之后析构时不再调用 `GBHouTai`
+    pub fn unbind(mut self) -> Result<()> {
+        self.bound = false;
+        self.aojia.GBHouTai().map(|_| ())
+    }
+
+    pub fn left_click(&self) -> Result<()> {
+        self.aojia.LeftClick().map(|_| ())
+    }
+
+    pub fn left_down(&self) -> Result<()> {
+        self.aojia.LeftDown().map(|_| ())
+    }
+
+    pub fn left_up(&self) -> Result<()> {
+        self.aojia.LeftUp().map(|_| ())
+    }
+
+    pub fn move_to(&self, point: Point) -> Result<()> {
+        self.aojia.MoveTo(point.x, point.y).map(|_| ())
+    }
+
+    /// 移动到 `point` 后单击
+    pub fn click_at(&self, point: Point) -> Result<()> {
+        self.move_to(point)?;
+        self.left_click()
+    }
+
+    pub fn wheel_down(&self) -> Result<()> {
+        self.aojia.WheelDown().map(|_| ())
+    }
+
+    /// 鼠标在绑定窗口中的位置
+    pub fn mouse_pos(&self) -> Result<Point> {
+        self.aojia.get_mouse_pos(0)
+    }
+
+    /// 绑定窗口客户区的大小
+    pub fn client_size(&self) -> Result<Size> {
+        self.aojia.get_client_size(self.hwnd)
+    }
+
+    /// 见 [`PicSearch::first`]
+    pub fn find_pic(&self, search: &PicSearch) -> Result<Option<PicMatch>> {
+        search.first(self.aojia)
+    }
+
+    /// 见 [`PicSearch::all`]
+    pub fn find_pic_ex(&self, search: &PicSearch) -> Result<Vec<PicMatch>> {
+        search.all(self.aojia)
+    }
+
+    /// 见 [`OcrRequest::run`]
+    pub fn ocr(&self, request: &OcrRequest) -> Result<OcrResult> {
+        request.run(self.aojia)
+    }
+}
+
+impl<B: Dispatch> fmt::Debug for BindingGuard<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BindingGuard")
+            .field("hwnd", &self.hwnd)
+            .field("modes", &self.modes)
+            .field("bound", &self.bound)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: Dispatch> Drop for BindingGuard<'_, B> {
+    fn drop(&mut self) {
+        if !self.bound {
+            return;
+        }
+        if let Err(e) = self.aojia.GBHouTai() {
+            (self.on_unbind_error)(e);
+        }
+    }
+}
+
+impl<B: Dispatch> AoJia<B> {
+    /// 绑定窗口，返回的句柄析构时自动解除绑定
+    ///
+    /// 析构时解除绑定失败的错误交给 `on_unbind_error`，包括因 panic 展开而析构时。
+    pub fn bind<'a>(
+        &'a self,
+        hwnd: Hwnd,
+        modes: &BindModes,
+        on_unbind_error: impl FnMut(AoJiaError) + 'a,
+    ) -> Result<BindingGuard<'a, B>> {
+        self.kq_hou_tai(hwnd, modes)?;
+        Ok(BindingGuard {
+            aojia: self,
+            hwnd,
+            modes: *modes,
+            on_unbind_error: Box::new(on_unbind_error),
+            bound: true,
+        })
+    }
+}
@@ -0,0 +1,210 @@
+use crate::dispatch::{Arg, Call, Dispatch, FromValue, Value};
+use crate::error::{AoJiaError, Result};
+use crate::{AoJia, BINDINGS};
+
+/// 把返回值转换为目标类型，失败时带上调用信息
+pub(crate) fn convert<T: FromValue>(name: &str, args: &[Arg], value: &Value) -> Result<T> {
+    T::from_value(value).map_err(|source| AoJiaError::Conversion {
+        call: Call::new(name, args),
+        source,
+    })
+}
+
+/// 把传址参数转换为目标类型，见 [`FromValue::from_out`]
+pub(crate) fn convert_out<T: FromValue>(
+    name: &str,
+    args: &[Arg],
+    value: Option<&Value>,
+) -> Result<T> {
+    T::from_out(value.unwrap_or(&Value::Empty)).map_err(|source| AoJiaError::Conversion {
+        call: Call::new(name, args),
+        source,
+    })
+}
+
+/// 返回值属于约定的失败码时报错，未返回值（`Value::Empty`）时不检查
+pub(crate) fn check_return(name: &str, args: &[Arg], ret: &Value, failure: &[i64]) -> Result<()> {
+    let code = match ret {
+        Value::Empty => return Ok(()),
+        _ => ret.to_i64(),
+    };
+    match code {
+        Ok(code) if failure.contains(&code) => Err(AoJiaError::Plugin {
+            call: Call::new(name, args),
+            code,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// 以 [`Args`] 给出的参数列表调用 `name`，与包装方法一样检查失败码并转换返回值
+pub(crate) fn call<B: Dispatch, T: FromValue>(
+    aojia: &AoJia<B>,
+    name: &str,
+    args: &mut [Arg],
+) -> Result<T> {
+    let failure = BINDINGS
+        .iter()
+        .find(|b| b.name == name)
+        .map_or(&[][..], |b| b.failure);
+    let ret = aojia.invoke(name, args)?;
+    check_return(name, args, &ret, failure)?;
+    convert(name, args, &ret)
+}
+
+/// 各包装方法的参数列表，由 [`bindings!`] 生成
+///
+/// 与包装方法同名，只接收传值参数，传址参数的位置为 `Arg::out()`。
+/// 配套方法据此构造错误中的 [`Call`]，不必另写一份参数列表。
+pub(crate) struct Args;
+
+/// 生成的包装方法的签名，见 [`BINDINGS`](crate::BINDINGS)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub name: &'static str,
+    pub params: &'static [BindingParam],
+    pub returns: &'static str,
+    /// 表示失败的返回值
+    pub failure: &'static [i64],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BindingParam {
+    pub name: &'static str,
+    /// 参数类型，传址参数为 `&mut` 之后的部分
+    pub ty: &'static str,
+    pub out: bool,
+}
+
+/// 由签名表生成 `AoJia` 的包装方法
+///
+/// 每一项按插件文档的参数顺序书写，`&mut T` 为传址参数，其余为传值参数。
+/// 参数个数、COM 的逆序排列以及传址参数的回写都由宏推导，无需手写。
+/// 签名表由 build.rs 依据 `spec/aojia.json` 生成。
+macro_rules! bindings {
+    ($(
+        $(#[doc = $doc:literal])*
+        $(#[cfg($cfg:meta)])?
+        $(#[failure($($fail:literal),*)])?
+        fn $name:ident($($params:tt)*) -> $ret:ty;
+    )*) => {
+        impl $crate::bindings::Args {
+            $(
+                bindings!(@munch args [$(#[cfg($cfg)])?] $name
+                    [$($($fail),*)?] $ret; [] [] [] [] []; $($params)*);
+            )*
+        }
+
+        impl<B: $crate::Dispatch> $crate::AoJia<B> {
+            $(
+                bindings!(@munch sync [$(#[doc = $doc])* $(#[cfg($cfg)])?] $name
+                    [$($($fail),*)?] $ret; [] [] [] [] []; $($params)*);
+            )*
+        }
+
+        #[cfg(feature = "async")]
+        impl $crate::AsyncAoJia {
+            $(
+                bindings!(@munch future [$(#[doc = $doc])* $(#[cfg($cfg)])?] $name
+                    [$($($fail),*)?] $ret; [] [] [] [] []; $($params)*);
+            )*
+        }
+
+        /// 所有生成的包装方法的签名
+        pub const BINDINGS: &[$crate::Binding] = &[$(
+            $(#[cfg($cfg)])?
+            $crate::Binding {
+                name: stringify!($name),
+                params: bindings!(@params []; $($params)*),
+                returns: stringify!($ret),
+                failure: &[$($($fail),*)?],
+            },
+        )*];
+    };
+
+    // 传址参数
+    (@munch $mode:ident $meta:tt $name:ident $fail:tt $ret:ty;
+        [$($sig:tt)*] [$($args:tt)*] [$($outs:tt)*] [$($in_sig:tt)*] [$($ins:tt)*];
+        $p:ident: &mut $t:ty $(, $($rest:tt)*)?) => {
+        bindings!(@munch $mode $meta $name $fail $ret;
+            [$($sig)* $p: &mut $t,]
+            [$($args)* $crate::Arg::out(),]
+            [$($outs)* $p,]
+            [$($in_sig)*]
+            [$($ins)*];
+            $($($rest)*)?);
+    };
+
+    // 传值参数
+    (@munch $mode:ident $meta:tt $name:ident $fail:tt $ret:ty;
+        [$($sig:tt)*] [$($args:tt)*] [$($outs:tt)*] [$($in_sig:tt)*] [$($ins:tt)*];
+        $p:ident: $t:ty $(, $($rest:tt)*)?) => {
+        bindings!(@munch $mode $meta $name $fail $ret;
+            [$($sig)* $p: $t,]
+            [$($args)* $crate::dispatch::IntoArg::into_arg($p),]
+            [$($outs)*]
+            [$($in_sig)* $p: $t,]
+            [$($ins)* $p,];
+            $($($rest)*)?);
+    };
+
+    (@munch args [$($meta:tt)*] $name:ident $fail:tt $ret:ty;
+        $sig:tt [$($args:tt)*] $outs:tt [$($in_sig:tt)*] $ins:tt;) => {
+        $($meta)*
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        pub(crate) fn $name($($in_sig)*) -> Vec<$crate::Arg> {
+            vec![$($args)*]
+        }
+    };
+
+    (@munch sync [$($meta:tt)*] $name:ident [$($fail:literal),*] $ret:ty;
+        [$($sig:tt)*] $args:tt [$($out:ident,)*] $in_sig:tt [$($ins:tt)*];) => {
+        $($meta)*
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        pub fn $name(&self, $($sig)*) -> $crate::Result<$ret> {
+            let args: &mut [$crate::Arg] = &mut $crate::bindings::Args::$name($($ins)*);
+            let ret = self.invoke(stringify!($name), args)?;
+            bindings!(@finish $name [$($fail),*] args ret [$($out,)*])
+        }
+    };
+
+    // 异步版本，参数在调用前转为自有值交给插件线程
+    (@munch future [$($meta:tt)*] $name:ident [$($fail:literal),*] $ret:ty;
+        [$($sig:tt)*] $args:tt [$($out:ident,)*] $in_sig:tt [$($ins:tt)*];) => {
+        $($meta)*
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        pub async fn $name(&self, $($sig)*) -> $crate::Result<$ret> {
+            let args = $crate::bindings::Args::$name($($ins)*);
+            let (ret, args) = self.invoke(stringify!($name), args).await?;
+            let args: &[$crate::Arg] = &args;
+            bindings!(@finish $name [$($fail),*] args ret [$($out,)*])
+        }
+    };
+
+    // 检查失败码，回写传址参数并转换返回值
+    (@finish $name:ident [$($fail:literal),*] $args:ident $ret:ident [$($out:ident,)*]) => {{
+        $crate::bindings::check_return(stringify!($name), $args, &$ret, &[$($fail),*])?;
+
+        #[allow(unused_mut, unused_variables)]
+        let mut outs = $args.iter().filter(|a| a.is_out()).map($crate::Arg::value);
+        $(*$out = $crate::bindings::convert_out(stringify!($name), $args, outs.next())?;)*
+
+        $crate::bindings::convert(stringify!($name), $args, &$ret)
+    }};
+
+    (@params [$($acc:tt)*]; $p:ident: &mut $t:ty $(, $($rest:tt)*)?) => {
+        bindings!(@params [$($acc)*
+            $crate::BindingParam { name: stringify!($p), ty: stringify!($t), out: true },];
+            $($($rest)*)?)
+    };
+
+    (@params [$($acc:tt)*]; $p:ident: $t:ty $(, $($rest:tt)*)?) => {
+        bindings!(@params [$($acc)*
+            $crate::BindingParam { name: stringify!($p), ty: stringify!($t), out: false },];
+            $($($rest)*)?)
+    };
+
+    (@params [$($acc:tt)*];) => {
+        &[$($acc)*]
+    };
+}
@@ -0,0 +1,210 @@
+//! 颜色及偏色描述
+//!
+//! 插件以 `RRGGBB` 表示颜色，以 `RRGGBB-DRDGDB` 表示颜色及其允许的偏色，
+//! 多个颜色之间以 `|` 分隔。
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::dispatch::{Arg, IntoArg};
+use crate::error::ParseError;
+
+/// 颜色，按 `RRGGBB` 格式读写
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(0xFF, 0xFF, 0xFF);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// 各通道之差都不超过 `delta` 的对应通道
+    pub fn within(self, other: Rgb, delta: Rgb) -> bool {
+        self.r.abs_diff(other.r) <= delta.r
+            && self.g.abs_diff(other.g) <= delta.g
+            && self.b.abs_diff(other.b) <= delta.b
+    }
+
+    /// 解析 `RRGGBB`，`what` 用于错误信息
+    fn parse(s: &str, what: &'static str, input: &str) -> Result<Self, ParseError> {
+        let s = s.trim();
+        if s.len() != 6 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseError::new(
+                what,
+                input,
+                format!("{s:?} 应为 6 位十六进制 RRGGBB"),
+            ));
+        }
+        let channel = |i: usize| u8::from_str_radix(&s[i..i + 2], 16).unwrap();
+        Ok(Self::new(channel(0), channel(2), channel(4)))
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, "颜色", s)
+    }
+}
+
+impl IntoArg for Rgb {
+    fn into_arg(self) -> Arg {
+        Arg::input(self.to_string())
+    }
+}
+
+/// 颜色及允许的偏色，按 `RRGGBB-DRDGDB` 格式读写
+///
+/// 解析时可省略偏色，视为 `000000`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ColorDelta {
+    pub color: Rgb,
+    pub delta: Rgb,
+}
+
+impl ColorDelta {
+    pub const fn new(color: Rgb, delta: Rgb) -> Self {
+        Self { color, delta }
+    }
+
+    /// 不允许偏色
+    pub const fn exact(color: Rgb) -> Self {
+        Self::new(color, Rgb::BLACK)
+    }
+
+    /// `pixel` 与颜色之差在偏色范围内
+    pub fn matches(&self, pixel: Rgb) -> bool {
+        pixel.within(self.color, self.delta)
+    }
+
+    fn parse(s: &str, what: &'static str, input: &str) -> Result<Self, ParseError> {
+        match s.split_once('-') {
+            Some((color, delta)) => Ok(Self::new(
+                Rgb::parse(color, what, input)?,
+                Rgb::parse(delta, what, input)?,
+            )),
+            None => Ok(Self::exact(Rgb::parse(s, what, input)?)),
+        }
+    }
+}
+
+impl From<Rgb> for ColorDelta {
+    fn from(color: Rgb) -> Self {
+        Self::exact(color)
+    }
+}
+
+impl fmt::Display for ColorDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.color, self.delta)
+    }
+}
+
+impl FromStr for ColorDelta {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, "偏色描述", s)
+    }
+}
+
+impl IntoArg for ColorDelta {
+    fn into_arg(self) -> Arg {
+        Arg::input(self.to_string())
+    }
+}
+
+/// 一组颜色，按 `RRGGBB-DRDGDB|...` 格式读写，至少包含一个颜色
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColorSpec {
+    colors: Vec<ColorDelta>,
+}
+
+impl ColorSpec {
+    pub fn new(color: impl Into<ColorDelta>) -> Self {
+        Self {
+            colors: vec![color.into()],
+        }
+    }
+
+    /// 追加一个颜色
+    pub fn or(mut self, color: impl Into<ColorDelta>) -> Self {
+        self.colors.push(color.into());
+        self
+    }
+
+    pub fn colors(&self) -> &[ColorDelta] {
+        &self.colors
+    }
+
+    /// `pixel` 符合其中任一颜色
+    pub fn matches(&self, pixel: Rgb) -> bool {
+        self.colors.iter().any(|c| c.matches(pixel))
+    }
+}
+
+impl From<Rgb> for ColorSpec {
+    fn from(color: Rgb) -> Self {
+        Self::new(color)
+    }
+}
+
+impl From<ColorDelta> for ColorSpec {
+    fn from(color: ColorDelta) -> Self {
+        Self::new(color)
+    }
+}
+
+impl fmt::Display for ColorSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, color) in self.colors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+            write!(f, "{color}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ColorSpec {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const WHAT: &str = "颜色描述";
+        if s.trim().is_empty() {
+            return Err(ParseError::new(WHAT, s, "至少需要一个颜色"));
+        }
+        let colors = s
+            .split('|')
+            .map(|item| ColorDelta::parse(item, WHAT, s))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { colors })
+    }
+}
+
+impl IntoArg for &ColorSpec {
+    fn into_arg(self) -> Arg {
+        Arg::input(self.to_string())
+    }
+}
+
+impl IntoArg for ColorSpec {
+    fn into_arg(self) -> Arg {
+        (&self).into_arg()
+    }
+}
@@ -0,0 +1,306 @@
+use once_cell::sync::OnceCell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ptr;
+use windows::{
+    Win32::{
+        Globalization::GetUserDefaultLCID,
+        System::{
+            Com::{
+                CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx,
+                CoUninitialize, DISPATCH_METHOD, DISPPARAMS, IDispatch,
+            },
+            LibraryLoader::{GetProcAddress, LoadLibraryW},
+            Variant::{
+                VAR_CHANGE_FLAGS, VARENUM, VARIANT, VARIANT_0_0, VT_BOOL, VT_BSTR, VT_BYREF,
+                VT_EMPTY, VT_I4, VT_I8, VT_NULL, VT_R4, VT_R8, VT_UI4, VT_UI8, VT_UINT, VT_VARIANT,
+                VariantChangeType, VariantClear,
+            },
+        },
+    },
+    core::{GUID, HSTRING, PCSTR, PCWSTR},
+};
+
+use std::mem::ManuallyDrop;
+
+use crate::dispatch::{Arg, Call, Dispatch, Value};
+use crate::error::{AoJiaError, ConversionError, Result};
+
+pub trait VariantExt {
+    fn by_ref(var_val: *mut VARIANT) -> VARIANT;
+    fn to_i32(&self) -> windows::core::Result<i32>;
+    fn to_i64(&self) -> windows::core::Result<i64>;
+    fn to_f64(&self) -> windows::core::Result<f64>;
+    fn to_string(&self) -> windows::core::Result<String>;
+    fn to_bool(&self) -> windows::core::Result<bool>;
+    fn from_str(s: &str) -> VARIANT;
+}
+
+impl VariantExt for VARIANT {
+    fn by_ref(var_val: *mut VARIANT) -> VARIANT {
+        let mut variant = VARIANT::default();
+        let mut v00 = VARIANT_0_0 {
+            vt: VARENUM(VT_BYREF.0 | VT_VARIANT.0),
+            ..Default::default()
+        };
+        v00.Anonymous.pvarVal = var_val;
+        variant.Anonymous.Anonymous = ManuallyDrop::new(v00);
+        variant
+    }
+    fn to_i32(&self) -> windows::core::Result<i32> {
+        unsafe {
+            let mut new = VARIANT::default();
+            VariantChangeType(&mut new, self, VAR_CHANGE_FLAGS(0), VT_I4)?;
+            let v00 = &new.Anonymous.Anonymous;
+            let n = v00.Anonymous.lVal;
+            VariantClear(&mut new)?;
+            Ok(n)
+        }
+    }
+    fn to_i64(&self) -> windows::core::Result<i64> {
+        unsafe {
+            let mut new = VARIANT::default();
+            VariantChangeType(&mut new, self, VAR_CHANGE_FLAGS(0), VT_I8)?;
+            let v00 = &new.Anonymous.Anonymous;
+            let n = v00.Anonymous.llVal;
+            VariantClear(&mut new)?;
+            Ok(n)
+        }
+    }
+    fn to_f64(&self) -> windows::core::Result<f64> {
+        unsafe {
+            let mut new = VARIANT::default();
+            VariantChangeType(&mut new, self, VAR_CHANGE_FLAGS(0), VT_R8)?;
+            let v00 = &new.Anonymous.Anonymous;
+            let f = v00.Anonymous.dblVal;
+            VariantClear(&mut new)?;
+            Ok(f)
+        }
+    }
+    fn to_string(&self) -> windows::core::Result<String> {
+        unsafe {
+            let mut new = VARIANT::default();
+            VariantChangeType(&mut new, self, VAR_CHANGE_FLAGS(0), VT_BSTR)?;
+            let v00 = &new.Anonymous.Anonymous;
+            let str = v00.Anonymous.bstrVal.to_string();
+            VariantClear(&mut new)?;
+            Ok(str)
+        }
+    }
+    fn to_bool(&self) -> windows::core::Result<bool> {
+        unsafe {
+            let mut new = VARIANT::default();
+            VariantChangeType(&mut new, self, VAR_CHANGE_FLAGS(0), VT_BOOL)?;
+            let v00 = &new.Anonymous.Anonymous;
+            let b = v00.Anonymous.boolVal.as_bool();
+            VariantClear(&mut new)?;
+            Ok(b)
+        }
+    }
+    fn from_str(s: &str) -> VARIANT {
+        if s.is_empty() {
+            VARIANT::default()
+        } else {
+            VARIANT::from(s)
+        }
+    }
+}
+
+fn to_variant(value: &Value) -> VARIANT {
+    match value {
+        Value::Empty => VARIANT::default(),
+        Value::Bool(b) => VARIANT::from(*b),
+        Value::I32(n) => VARIANT::from(*n),
+        Value::I64(n) => VARIANT::from(*n),
+        Value::F64(f) => VARIANT::from(*f),
+        Value::Str(s) => VARIANT::from(s.as_str()),
+    }
+}
+
+fn from_variant(var: &VARIANT) -> std::result::Result<Value, ConversionError> {
+    let vt = unsafe { var.Anonymous.Anonymous.vt };
+    let failed = |target, e: windows::core::Error| {
+        ConversionError::new(format!("VARENUM({}) [{}]", vt.0, e.message()), target)
+    };
+    Ok(match vt {
+        VT_EMPTY | VT_NULL => Value::Empty,
+        VT_BOOL => Value::Bool(var.to_bool().map_err(|e| failed("bool", e))?),
+        VT_I8 | VT_UI8 | VT_UI4 | VT_UINT => {
+            Value::I64(var.to_i64().map_err(|e| failed("i64", e))?)
+        }
+        VT_R4 | VT_R8 => Value::F64(var.to_f64().map_err(|e| failed("f64", e))?),
+        VT_BSTR => Value::Str(VariantExt::to_string(var).map_err(|e| failed("String", e))?),
+        _ => Value::I32(var.to_i32().map_err(|e| failed("i32", e))?),
+    })
+}
+
+// 对应 CARegJ 类
+type FnSetDllPathW = unsafe extern "system" fn(PCWSTR, i32) -> i32;
+static PFN_SET_DLL_PATH_W: OnceCell<std::result::Result<FnSetDllPathW, String>> = OnceCell::new();
+
+fn set_dll_path(a_regj_path: String, ao_jia_path: String) -> Result<()> {
+    let pfn = PFN_SET_DLL_PATH_W.get_or_init(|| unsafe {
+        let a_regj_hstring = HSTRING::from(a_regj_path.as_str());
+        let hmodule =
+            LoadLibraryW(PCWSTR::from_raw(a_regj_hstring.as_ptr())).map_err(|e| e.message())?;
+        let proc_name = PCSTR::from_raw(b"SetDllPathW\0".as_ptr());
+        GetProcAddress(hmodule, proc_name)
+            .map(|addr| std::mem::transmute::<_, FnSetDllPathW>(addr))
+            .ok_or_else(|| String::from("找不到导出函数 SetDllPathW"))
+    });
+
+    let error = |reason: String| AoJiaError::DllLoad {
+        a_regj_path: a_regj_path.clone(),
+        ao_jia_path: ao_jia_path.clone(),
+        reason,
+    };
+    let func = pfn.as_ref().map_err(|reason| error(reason.clone()))?;
+    let ret = unsafe {
+        let ao_jia_hstring = HSTRING::from(ao_jia_path.as_str());
+        func(PCWSTR::from_raw(ao_jia_hstring.as_ptr()), 0)
+    };
+    if ret == 0 {
+        return Err(error(String::from("SetDllPathW 返回 0")));
+    }
+    Ok(())
+}
+
+fn com_error(name: &str, args: &[Arg], e: windows::core::Error) -> AoJiaError {
+    AoJiaError::com(Call::new(name, args), e.code().0, e.message())
+}
+
+/// 通过 IDispatch 调用已注册的 AoJia64.dll
+#[derive(Debug)]
+pub struct ComBackend {
+    p_idispatch: Option<IDispatch>,
+    // 方法名到 DISPID 的缓存，避免每次调用都 GetIDsOfNames
+    dispids: RefCell<HashMap<String, i32>>,
+}
+
+impl ComBackend {
+    const CLSID: GUID = GUID::from_values(
+        0x4f27e588,
+        0x5b1e,
+        0x45b4,
+        [0xad, 0x67, 0xe3, 0x2d, 0x45, 0xc4, 0xe9, 0xca],
+    );
+
+    fn new() -> Result<Self> {
+        unsafe {
+            let hr = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            if hr.is_err() {
+                return Err(com_error("CoInitializeEx", &[], hr.into()));
+            }
+
+            let idispatch: IDispatch = CoCreateInstance(&Self::CLSID, None, CLSCTX_INPROC_SERVER)
+                .map_err(|e| com_error("CoCreateInstance", &[], e))?;
+
+            Ok(Self {
+                p_idispatch: Some(idispatch),
+                dispids: RefCell::default(),
+            })
+        }
+    }
+
+    pub fn new_with_path(a_regj_path: String, ao_jia_path: String) -> Result<Self> {
+        set_dll_path(a_regj_path, ao_jia_path)?;
+        Self::new()
+    }
+
+    fn dispid(&self, name: &str) -> windows::core::Result<i32> {
+        if let Some(id) = self.dispids.borrow().get(name) {
+            return Ok(*id);
+        }
+
+        let fun_name = HSTRING::from(name);
+        let mut disp_id = -1;
+        unsafe {
+            let names_ptr = PCWSTR::from_raw(fun_name.as_ptr());
+            let names = [names_ptr];
+            self.p_idispatch.as_ref().unwrap().GetIDsOfNames(
+                &GUID::default(),
+                names.as_ptr(),
+                1,
+                GetUserDefaultLCID(),
+                &mut disp_id,
+            )?;
+        }
+        self.dispids.borrow_mut().insert(name.to_string(), disp_id);
+        Ok(disp_id)
+    }
+}
+
+impl Dispatch for ComBackend {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        let disp_id = self.dispid(name).map_err(|e| com_error(name, args, e))?;
+        let mut var_result = VARIANT::default();
+
+        // 传址参数的存储区，调用期间地址不能变化
+        let mut refs: Vec<VARIANT> = args.iter().map(|_| VARIANT::default()).collect();
+
+        // 按照COM调用约定，参数顺序是反向的
+        let mut rgvarg: Vec<VARIANT> = args
+            .iter()
+            .zip(refs.iter_mut())
+            .rev()
+            .map(|(arg, slot)| match arg {
+                Arg::In(value) => to_variant(value),
+                Arg::Out(_) => VARIANT::by_ref(slot as *mut VARIANT),
+            })
+            .collect();
+
+        let disp_params = DISPPARAMS {
+            rgvarg: if rgvarg.is_empty() {
+                ptr::null_mut()
+            } else {
+                rgvarg.as_mut_ptr()
+            },
+            rgdispidNamedArgs: ptr::null_mut(),
+            cArgs: rgvarg.len() as u32,
+            cNamedArgs: 0,
+        };
+
+        let invoked = unsafe {
+            self.p_idispatch.as_ref().unwrap().Invoke(
+                disp_id,
+                &GUID::default(),
+                GetUserDefaultLCID(),
+                DISPATCH_METHOD,
+                &disp_params,
+                Some(&mut var_result),
+                None,
+                None,
+            )
+        };
+        invoked.map_err(|e| com_error(name, args, e))?;
+
+        let call_args = args.to_vec();
+        let conversion = |source| AoJiaError::Conversion {
+            call: Call::new(name, &call_args),
+            source,
+        };
+        for (arg, slot) in args.iter_mut().zip(&refs) {
+            if let Arg::Out(value) = arg {
+                *value = from_variant(slot).map_err(conversion)?;
+            }
+        }
+        from_variant(&var_result).map_err(conversion)
+    }
+
+    fn resolve(&self, name: &str) -> Result<()> {
+        self.dispid(name)
+            .map(|_| ())
+            .map_err(|e| com_error(name, &[], e))
+    }
+}
+
+impl Drop for ComBackend {
+    fn drop(&mut self) {
+        unsafe {
+            // IDispatch implements Drop which will call Release internally
+            // Just let it drop automatically
+            self.p_idispatch.take();
+            CoUninitialize();
+        }
+    }
+}
@@ -0,0 +1,537 @@
+//! `LoadDict` 使用的字库文件
+//!
+//! 插件文档没有给出字库格式，这里按与大漠插件相同的文本格式处理，每行一个字：
+//!
+//! ```text
+//! 0001FFC00400$一$0.0.10$11
+//! ```
+//!
+//! 依次为点阵、字、附加信息及高度，以 `$` 分隔：
+//!
+//! - 点阵逐列自上而下排列，每列 `高度` 个点，第一个点为最高位，
+//!   整体转为十六进制，末尾不足 4 位时补 0，宽度为 `位数 / 高度` 向下取整；
+//!   高度不足 4 时补的 0 可能多于一列，此时按附加信息末段的点数确定宽度；
+//! - 附加信息原样保留，新建的条目为 `0.0.点数`。
+//!
+//! Windows 下生成的字库通常为 GBK 编码，带 UTF-8 BOM 的文件按 UTF-8 读取，
+//! 不带 BOM 的 UTF-8 文件须以 [`Dict::parse_with`] 指定编码。空行被跳过，
+//! 解析后原样写回时与原文件逐字节相同，包括编码、BOM、空行、每行的换行符、
+//! 末尾是否换行及十六进制的大小写。
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use encoding_rs::{DecoderResult, GBK};
+
+use crate::error::ParseError;
+
+const WHAT: &str = "字库";
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 单个字的点阵，`true` 为文字的点
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    /// 逐列存储
+    bits: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![false; width as usize * height as usize],
+        }
+    }
+
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(u32, u32) -> bool) -> Self {
+        let bits = (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            bits,
+        }
+    }
+
+    /// 由字库中的十六进制点阵及高度构造
+    ///
+    /// 高度不足 4 时宽度可能有多种，此时返回错误，需改用 [`Bitmap::from_hex_with_points`]。
+    pub fn from_hex(hex: &str, height: u32) -> Result<Self, ParseError> {
+        Self::decode(hex, height, None)
+    }
+
+    /// 同 [`Bitmap::from_hex`]，宽度有多种可能时取点数为 `points` 的最小宽度
+    pub fn from_hex_with_points(hex: &str, height: u32, points: usize) -> Result<Self, ParseError> {
+        Self::decode(hex, height, Some(points))
+    }
+
+    fn decode(hex: &str, height: u32, points: Option<usize>) -> Result<Self, ParseError> {
+        let err = |reason: String| ParseError::new("字库点阵", hex, reason);
+        if height == 0 {
+            return Err(err("高度不能为 0".to_string()));
+        }
+        let mut bits = Vec::with_capacity(hex.len() * 4);
+        for c in hex.chars() {
+            let digit = c
+                .to_digit(16)
+                .ok_or_else(|| err(format!("{c:?} 不是十六进制数字")))?;
+            bits.extend((0..4).rev().map(|i| digit >> i & 1 == 1));
+        }
+        // 补位不足 4 位，因此宽度 w 满足 位数 - 4 < w * 高度 <= 位数
+        let h = height as usize;
+        let max = bits.len() / h;
+        let min = (bits.len().saturating_sub(3)).div_ceil(h).min(max);
+        let width = if min == max {
+            max
+        } else {
+            let points = points.ok_or_else(|| {
+                err(format!(
+                    "高度为 {height} 时宽度可能为 {min} 至 {max}，无法确定"
+                ))
+            })?;
+            (min..=max)
+                .find(|w| bits[..w * h].iter().filter(|&&b| b).count() == points)
+                .ok_or_else(|| err(format!("宽度为 {min} 至 {max} 时点数均不为 {points}")))?
+        };
+        bits.truncate(width * h);
+        let width = width as u32;
+        Ok(Self {
+            width,
+            height,
+            bits,
+        })
+    }
+
+    /// 转为字库中的十六进制点阵，大写，末尾补 0
+    pub fn to_hex(&self) -> String {
+        self.bits
+            .chunks(4)
+            .map(|chunk| {
+                let digit = (0..4).fold(0, |n, i| {
+                    n << 1 | chunk.get(i).copied().unwrap_or(false) as u32
+                });
+                char::from_digit(digit, 16).unwrap().to_ascii_uppercase()
+            })
+            .collect()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        x as usize * self.height as usize + y as usize
+    }
+
+    /// 坐标超出点阵时为 `false`
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.bits[self.index(x, y)]
+    }
+
+    /// 坐标超出点阵时 panic
+    pub fn set(&mut self, x: u32, y: u32, on: bool) {
+        assert!(x < self.width && y < self.height, "({x}, {y}) 超出点阵");
+        let i = self.index(x, y);
+        self.bits[i] = on;
+    }
+
+    /// 文字的点数
+    pub fn points(&self) -> usize {
+        self.bits.iter().filter(|&&b| b).count()
+    }
+}
+
+/// 逐行以 `#` 与 `.` 画出点阵，便于审阅
+impl fmt::Display for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            if y > 0 {
+                f.write_str("\n")?;
+            }
+            for x in 0..self.width {
+                f.write_str(if self.get(x, y) { "#" } else { "." })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 换行符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Newline {
+    /// `\r\n`，Windows 下生成的字库
+    #[default]
+    CrLf,
+    Lf,
+}
+
+impl Newline {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Newline::CrLf => "\r\n",
+            Newline::Lf => "\n",
+        }
+    }
+}
+
+/// 字库文件的编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DictEncoding {
+    /// Windows 下生成的字库
+    #[default]
+    Gbk,
+    Utf8,
+}
+
+/// 字库中的一个字
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictEntry {
+    ch: String,
+    bitmap: Bitmap,
+    meta: String,
+    /// 文件中的点阵原文，与 `to_hex` 不同时（如小写）写回原文，修改点阵后清空
+    hex: Option<String>,
+    /// 该行原有的换行符
+    newline: Option<Newline>,
+    /// 该行之前的空行，含换行符
+    blank: String,
+}
+
+impl DictEntry {
+    /// 字库允许以多个字符作为一个字
+    pub fn new(ch: impl Into<String>, bitmap: Bitmap) -> Self {
+        Self {
+            ch: ch.into(),
+            meta: format!("0.0.{}", bitmap.points()),
+            bitmap,
+            hex: None,
+            newline: None,
+            blank: String::new(),
+        }
+    }
+
+    pub fn ch(&self) -> &str {
+        &self.ch
+    }
+
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    /// 附加信息，即第三段
+    pub fn meta(&self) -> &str {
+        &self.meta
+    }
+
+    pub fn set_ch(&mut self, ch: impl Into<String>) {
+        self.ch = ch.into();
+    }
+
+    pub fn set_meta(&mut self, meta: impl Into<String>) {
+        self.meta = meta.into();
+    }
+
+    /// 替换点阵，附加信息中的点数不会随之更新
+    pub fn set_bitmap(&mut self, bitmap: Bitmap) {
+        self.bitmap = bitmap;
+        self.hex = None;
+    }
+
+    fn parse(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.split('$').collect();
+        let [hex, ch, meta, height] = fields[..] else {
+            return Err("应为 点阵$字$附加信息$高度".to_string());
+        };
+        let height: u32 = height
+            .parse()
+            .map_err(|_| format!("{height:?} 不是有效的高度"))?;
+        // 附加信息末段为点数，用于确定高度不足 4 时的宽度
+        let bitmap = match meta.rsplit('.').next().and_then(|n| n.parse().ok()) {
+            Some(points) => Bitmap::from_hex_with_points(hex, height, points),
+            None => Bitmap::from_hex(hex, height),
+        }
+        .map_err(|e| e.reason)?;
+        Ok(Self {
+            ch: ch.to_string(),
+            meta: meta.to_string(),
+            hex: (bitmap.to_hex() != hex).then(|| hex.to_string()),
+            bitmap,
+            newline: None,
+            blank: String::new(),
+        })
+    }
+
+    fn write(&self, out: &mut String) {
+        match &self.hex {
+            Some(hex) => out.push_str(hex),
+            None => out.push_str(&self.bitmap.to_hex()),
+        }
+        out.push('$');
+        out.push_str(&self.ch);
+        out.push('$');
+        out.push_str(&self.meta);
+        out.push('$');
+        out.push_str(&self.bitmap.height.to_string());
+    }
+}
+
+impl fmt::Display for DictEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut line = String::new();
+        self.write(&mut line);
+        f.write_str(&line)
+    }
+}
+
+/// 字库文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dict {
+    entries: Vec<DictEntry>,
+    /// 新增条目使用的换行符，解析时取第一行的换行符
+    newline: Newline,
+    encoding: DictEncoding,
+    bom: bool,
+    /// 最后一个字之后是否有换行
+    final_newline: bool,
+    /// 最后一个字之后的空行
+    trailing: String,
+}
+
+impl Default for Dict {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dict {
+    /// 空字库，以 `\r\n` 换行，GBK 编码
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            newline: Newline::default(),
+            encoding: DictEncoding::default(),
+            bom: false,
+            final_newline: true,
+            trailing: String::new(),
+        }
+    }
+
+    /// 带 UTF-8 BOM 时按 UTF-8 解析，否则按 GBK 解析
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        Self::parse_with(bytes, DictEncoding::Gbk)
+    }
+
+    /// 按 `encoding` 解析，带 UTF-8 BOM 时总是按 UTF-8 解析
+    pub fn parse_with(bytes: &[u8], encoding: DictEncoding) -> Result<Self, ParseError> {
+        let (encoding, bom, bytes) = match bytes.strip_prefix(BOM) {
+            Some(rest) => (DictEncoding::Utf8, true, rest),
+            None => (encoding, false, bytes),
+        };
+        let text = match encoding {
+            DictEncoding::Utf8 => std::str::from_utf8(bytes)
+                .map(str::to_string)
+                .map_err(|e| {
+                    ParseError::new(
+                        WHAT,
+                        &String::from_utf8_lossy(
+                            &bytes[e.valid_up_to().saturating_sub(32)..e.valid_up_to()],
+                        ),
+                        format!("第 {} 字节起不是 UTF-8 编码", e.valid_up_to() + 1),
+                    )
+                })?,
+            DictEncoding::Gbk => decode_gbk(bytes)?,
+        };
+
+        let mut dict = Self {
+            encoding,
+            bom,
+            ..Self::new()
+        };
+        let mut blank = String::new();
+        for (i, raw) in text.split_inclusive('\n').enumerate() {
+            let mut line = raw;
+            let newline = if let Some(rest) = line.strip_suffix("\r\n") {
+                line = rest;
+                Some(Newline::CrLf)
+            } else if let Some(rest) = line.strip_suffix('\n') {
+                line = rest;
+                Some(Newline::Lf)
+            } else {
+                None
+            };
+            if i == 0 {
+                dict.newline = newline.unwrap_or_default();
+            }
+            if line.trim().is_empty() {
+                blank.push_str(raw);
+                continue;
+            }
+            dict.final_newline = newline.is_some();
+            let mut entry = DictEntry::parse(line).map_err(|reason| {
+                ParseError::new(WHAT, line, format!("第 {} 行：{reason}", i + 1))
+            })?;
+            entry.newline = newline;
+            entry.blank = std::mem::take(&mut blank);
+            dict.entries.push(entry);
+        }
+        dict.trailing = blank;
+        Ok(dict)
+    }
+
+    /// 读取字库文件，编码的判断与 [`Dict::parse`] 相同
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open_with(path, DictEncoding::Gbk)
+    }
+
+    pub fn open_with(path: impl AsRef<Path>, encoding: DictEncoding) -> io::Result<Self> {
+        Self::parse_with(&fs::read(path)?, encoding)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// 按字库的编码生成文件内容，GBK 字库中有 GBK 无法表示的字时返回 `InvalidData`
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut out = String::new();
+        if self.bom {
+            out.push('\u{FEFF}');
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            out.push_str(&entry.blank);
+            entry.write(&mut out);
+            if i + 1 < self.entries.len() || self.final_newline {
+                out.push_str(entry.newline.unwrap_or(self.newline).as_str());
+            }
+        }
+        out.push_str(&self.trailing);
+        match self.encoding {
+            DictEncoding::Utf8 => Ok(out.into_bytes()),
+            DictEncoding::Gbk => encode_gbk(&out),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes()?)
+    }
+
+    /// 新增条目使用的换行符
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// 写回时使用的编码，UTF-8 时带 BOM，以便 [`Dict::parse`] 识别
+    pub fn encoding(mut self, encoding: DictEncoding) -> Self {
+        self.encoding = encoding;
+        self.bom = encoding == DictEncoding::Utf8;
+        self
+    }
+
+    pub fn entries(&self) -> &[DictEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 第一个为 `ch` 的条目
+    pub fn get(&self, ch: &str) -> Option<&DictEntry> {
+        self.entries.iter().find(|e| e.ch == ch)
+    }
+
+    pub fn get_mut(&mut self, ch: &str) -> Option<&mut DictEntry> {
+        self.entries.iter_mut().find(|e| e.ch == ch)
+    }
+
+    pub fn push(&mut self, entry: DictEntry) {
+        self.entries.push(entry);
+    }
+
+    /// 追加 `other` 中点阵尚未出现的条目，点阵相同时以本字库为准，返回追加的个数
+    pub fn merge(&mut self, other: &Dict) -> usize {
+        let mut added = 0;
+        for entry in &other.entries {
+            if !self.entries.iter().any(|e| e.bitmap == entry.bitmap) {
+                self.push(DictEntry {
+                    newline: None,
+                    blank: String::new(),
+                    ..entry.clone()
+                });
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// 删除所有为 `ch` 的条目，返回删除的个数
+    pub fn remove(&mut self, ch: &str) -> usize {
+        let before = self.entries.len();
+        self.retain(|e| e.ch != ch);
+        before - self.entries.len()
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(&DictEntry) -> bool) {
+        self.entries.retain(f);
+    }
+
+    /// 把所有为 `from` 的条目改为 `to`，返回修改的个数
+    pub fn rename(&mut self, from: &str, to: &str) -> usize {
+        let mut renamed = 0;
+        for entry in self.entries.iter_mut().filter(|e| e.ch == from) {
+            entry.ch = to.to_string();
+            renamed += 1;
+        }
+        renamed
+    }
+}
+
+fn decode_gbk(bytes: &[u8]) -> Result<String, ParseError> {
+    let mut decoder = GBK.new_decoder_without_bom_handling();
+    let capacity = decoder
+        .max_utf8_buffer_length_without_replacement(bytes.len())
+        .unwrap_or(bytes.len() * 3);
+    let mut text = String::with_capacity(capacity);
+    let (result, read) = decoder.decode_to_string_without_replacement(bytes, &mut text, true);
+    match result {
+        DecoderResult::InputEmpty => Ok(text),
+        DecoderResult::Malformed(bad, after) => {
+            let at = read - bad as usize - after as usize;
+            let before: String = text.chars().rev().take(16).collect();
+            Err(ParseError::new(
+                WHAT,
+                &before.chars().rev().collect::<String>(),
+                format!("第 {} 字节起不是 GBK 编码", at + 1),
+            ))
+        }
+        DecoderResult::OutputFull => unreachable!("已按最大长度分配"),
+    }
+}
+
+fn encode_gbk(text: &str) -> io::Result<Vec<u8>> {
+    let (bytes, _, unmappable) = GBK.encode(text);
+    if unmappable {
+        let c = text
+            .chars()
+            .find(|c| GBK.encode(c.encode_utf8(&mut [0; 4])).2)
+            .unwrap_or_default();
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{c:?} 无法以 GBK 编码，可改用 UTF-8"),
+        ));
+    }
+    Ok(bytes.into_owned())
+}
@@ -0,0 +1,359 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ConversionError, Result};
+
+type Converted<T> = std::result::Result<T, ConversionError>;
+
+/// 与平台无关的参数/返回值，对应 VARIANT 中插件用到的几种类型
+///
+/// 序列化为对应的 JSON 值，`Empty` 为 `null`；整数优先还原为 `I32`。
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    #[default]
+    Empty,
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    F64(f64),
+    Str(String),
+}
+
+impl Value {
+    /// 空字符串按 VT_EMPTY 传递，与 `VariantExt::from_str` 一致
+    pub fn opt_str(s: &str) -> Self {
+        if s.is_empty() {
+            Value::Empty
+        } else {
+            Value::from(s)
+        }
+    }
+
+    pub fn to_i32(&self) -> Converted<i32> {
+        let n = self.to_i64().map_err(|_| self.mismatch("i32"))?;
+        i32::try_from(n).map_err(|_| self.mismatch("i32"))
+    }
+
+    pub fn to_i64(&self) -> Converted<i64> {
+        match self {
+            Value::Empty => Ok(0),
+            Value::Bool(b) => Ok(if *b { -1 } else { 0 }),
+            Value::I32(n) => Ok(*n as i64),
+            Value::I64(n) => Ok(*n),
+            Value::F64(f) => Self::round(*f).ok_or_else(|| self.mismatch("i64")),
+            Value::Str(s) => {
+                let s = s.trim();
+                s.parse::<i64>()
+                    .ok()
+                    .or_else(|| s.parse::<f64>().ok().and_then(Self::round))
+                    .ok_or_else(|| self.mismatch("i64"))
+            }
+        }
+    }
+
+    pub fn to_f64(&self) -> Converted<f64> {
+        match self {
+            Value::Empty => Ok(0.0),
+            Value::Bool(b) => Ok(if *b { -1.0 } else { 0.0 }),
+            Value::I32(n) => Ok(*n as f64),
+            Value::I64(n) => Ok(*n as f64),
+            Value::F64(f) => Ok(*f),
+            Value::Str(s) => s.trim().parse().map_err(|_| self.mismatch("f64")),
+        }
+    }
+
+    pub fn to_string(&self) -> Converted<String> {
+        Ok(match self {
+            Value::Empty => String::new(),
+            Value::Bool(b) => if *b { "-1" } else { "0" }.to_string(),
+            Value::I32(n) => n.to_string(),
+            Value::I64(n) => n.to_string(),
+            Value::F64(f) => f.to_string(),
+            Value::Str(s) => s.clone(),
+        })
+    }
+
+    pub fn to_bool(&self) -> Converted<bool> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            Value::Str(s) if s.trim().eq_ignore_ascii_case("true") => Ok(true),
+            Value::Str(s) if s.trim().eq_ignore_ascii_case("false") => Ok(false),
+            _ => self.to_f64().map(|f| f != 0.0),
+        }
+    }
+
+    // VariantChangeType 对浮点数按银行家舍入
+    fn round(f: f64) -> Option<i64> {
+        let r = f.round_ties_even();
+        (r >= i64::MIN as f64 && r < i64::MAX as f64).then_some(r as i64)
+    }
+
+    fn mismatch(&self, ty: &'static str) -> ConversionError {
+        ConversionError::new(format!("{self}"), ty)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::I32(n)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::I64(n)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(f: f64) -> Self {
+        Value::F64(f)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Empty => f.write_str("empty"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::I32(n) => write!(f, "{n}"),
+            Value::I64(n) => write!(f, "{n}"),
+            Value::F64(x) => write!(f, "{x}"),
+            Value::Str(s) => write!(f, "{s:?}"),
+        }
+    }
+}
+
+/// 一次调用中的单个参数
+#[derive(Debug, Clone, PartialEq)]
+pub enum Arg {
+    /// 传值参数
+    In(Value),
+    /// 传址参数，调用后由后端回写
+    Out(Value),
+}
+
+impl Arg {
+    pub fn input(value: impl Into<Value>) -> Self {
+        Arg::In(value.into())
+    }
+
+    pub fn out() -> Self {
+        Arg::Out(Value::Empty)
+    }
+
+    pub fn value(&self) -> &Value {
+        match self {
+            Arg::In(v) | Arg::Out(v) => v,
+        }
+    }
+
+    pub fn is_out(&self) -> bool {
+        matches!(self, Arg::Out(_))
+    }
+}
+
+/// 一次调用的方法名及参数，用于记录和报错
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub name: String,
+    /// 调用时传入的参数，传址参数为调用前的值
+    pub args: Vec<Arg>,
+}
+
+impl Call {
+    pub fn new(name: &str, args: &[Arg]) -> Self {
+        Self {
+            name: name.to_string(),
+            args: args.to_vec(),
+        }
+    }
+
+    pub fn arg(&self, index: usize) -> &Value {
+        self.args[index].value()
+    }
+
+    /// 仅传值参数，便于断言
+    pub fn inputs(&self) -> Vec<&Value> {
+        self.args
+            .iter()
+            .filter(|a| !a.is_out())
+            .map(Arg::value)
+            .collect()
+    }
+}
+
+impl fmt::Display for Call {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match arg {
+                Arg::In(v) => write!(f, "{v}")?,
+                Arg::Out(_) => f.write_str("&out")?,
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+/// 按名称调用插件方法的后端
+///
+/// `args` 按方法声明的顺序排列（而非 COM 的逆序），
+/// 后端负责在调用后把结果写回其中的 [`Arg::Out`]，
+/// 出错时返回的 [`AoJiaError`](crate::AoJiaError) 应携带本次调用的 [`Call`]。
+pub trait Dispatch {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value>;
+
+    /// 确认插件提供名为 `name` 的方法，后端可借此预先解析并缓存
+    fn resolve(&self, name: &str) -> Result<()> {
+        let _ = name;
+        Ok(())
+    }
+}
+
+impl<D: Dispatch + ?Sized> Dispatch for &D {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        (**self).invoke(name, args)
+    }
+
+    fn resolve(&self, name: &str) -> Result<()> {
+        (**self).resolve(name)
+    }
+}
+
+impl<D: Dispatch + ?Sized> Dispatch for Box<D> {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        (**self).invoke(name, args)
+    }
+
+    fn resolve(&self, name: &str) -> Result<()> {
+        (**self).resolve(name)
+    }
+}
+
+impl<D: Dispatch + ?Sized> Dispatch for Arc<D> {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        (**self).invoke(name, args)
+    }
+
+    fn resolve(&self, name: &str) -> Result<()> {
+        (**self).resolve(name)
+    }
+}
+
+/// 可作为传值参数的类型
+pub trait IntoArg {
+    fn into_arg(self) -> Arg;
+}
+
+impl IntoArg for i32 {
+    fn into_arg(self) -> Arg {
+        Arg::input(self)
+    }
+}
+
+impl IntoArg for i64 {
+    fn into_arg(self) -> Arg {
+        Arg::input(self)
+    }
+}
+
+impl IntoArg for f64 {
+    fn into_arg(self) -> Arg {
+        Arg::input(self)
+    }
+}
+
+impl IntoArg for bool {
+    fn into_arg(self) -> Arg {
+        Arg::input(self)
+    }
+}
+
+impl IntoArg for &str {
+    fn into_arg(self) -> Arg {
+        Arg::In(Value::opt_str(self))
+    }
+}
+
+/// 可由返回值或传址参数转换得到的类型
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Converted<Self>;
+
+    /// 由调用后的传址参数转换，插件没有写回（仍为 `Empty`）时报错，
+    /// 以免得到并不存在的 0 坐标等
+    fn from_out(value: &Value) -> Converted<Self> {
+        match value {
+            Value::Empty => Err(ConversionError::new(
+                "未写回的传址参数",
+                std::any::type_name::<Self>(),
+            )),
+            _ => Self::from_value(value),
+        }
+    }
+}
+
+impl FromValue for i32 {
+    fn from_value(value: &Value) -> Converted<Self> {
+        value.to_i32()
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Converted<Self> {
+        value.to_i64()
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Converted<Self> {
+        value.to_f64()
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Converted<Self> {
+        value.to_bool()
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Converted<Self> {
+        value.to_string()
+    }
+
+    // 空字符串本就按 VT_EMPTY 传递
+    fn from_out(value: &Value) -> Converted<Self> {
+        value.to_string()
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Converted<Self> {
+        Ok(value.clone())
+    }
+}
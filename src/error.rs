@@ -0,0 +1,201 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::dispatch::Call;
+
+pub type Result<T> = std::result::Result<T, AoJiaError>;
+
+/// 为以整数传给插件的模式枚举实现 `TryFrom<i32>`，未知的值返回 `ParseError`
+macro_rules! try_from_i32 {
+    ($name:ident, $what:literal { $($variant:ident),* $(,)? }) => {
+        impl TryFrom<i32> for $name {
+            type Error = $crate::error::ParseError;
+
+            fn try_from(value: i32) -> std::result::Result<Self, Self::Error> {
+                [$($name::$variant),*]
+                    .into_iter()
+                    .find(|v| *v as i32 == value)
+                    .ok_or_else(|| {
+                        let valid: Vec<String> =
+                            [$($name::$variant as i32),*].iter().map(i32::to_string).collect();
+                        $crate::error::ParseError::new(
+                            $what,
+                            &value.to_string(),
+                            format!("应为 {} 之一", valid.join("、")),
+                        )
+                    })
+            }
+        }
+    };
+}
+
+/// 返回值或传址参数无法转换为目标类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    /// 原始值的描述
+    pub value: String,
+    pub target: &'static str,
+}
+
+impl ConversionError {
+    pub fn new(value: impl Into<String>, target: &'static str) -> Self {
+        Self {
+            value: value.into(),
+            target,
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "无法将 {} 转换为 {}", self.value, self.target)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// 插件返回的文本或配置字符串格式不正确
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 正在解析的内容，如 "FindPicEx 结果"
+    pub what: &'static str,
+    pub input: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(what: &'static str, input: &str, reason: impl Into<String>) -> Self {
+        Self {
+            what,
+            input: input.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} 格式错误：{}",
+            self.what, self.input, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum AoJiaError {
+    /// COM 调用失败
+    Com {
+        call: Call,
+        hresult: i32,
+        message: String,
+    },
+    /// 加载 ARegJ64.dll 或设置插件路径失败
+    DllLoad {
+        a_regj_path: String,
+        ao_jia_path: String,
+        reason: String,
+    },
+    /// 返回值或传址参数的类型转换失败
+    Conversion { call: Call, source: ConversionError },
+    /// 插件返回的文本无法解析
+    Parse { call: Call, source: ParseError },
+    /// 插件返回了该函数约定的失败码，见 `spec/aojia.json` 中的 `failure`
+    Plugin { call: Call, code: i64 },
+    /// 参数组合无效，未调用插件
+    InvalidArgument { call: Call, source: ParseError },
+    /// 在限定时间内没有得到结果
+    Timeout { call: Call, timeout: Duration },
+    /// 等待的条件在限定时间内没有满足
+    WaitTimeout {
+        what: String,
+        /// 检查的次数
+        polls: u32,
+        elapsed: Duration,
+    },
+    /// 等待被取消
+    Cancelled { what: String, polls: u32 },
+    /// 重放时调用与日志不一致
+    Replay { call: Call, reason: String },
+}
+
+impl AoJiaError {
+    pub fn com(call: Call, hresult: i32, message: impl Into<String>) -> Self {
+        AoJiaError::Com {
+            call,
+            hresult,
+            message: message.into(),
+        }
+    }
+
+    /// 出错的调用，DLL 加载失败或等待超时、取消时为 `None`
+    pub fn call(&self) -> Option<&Call> {
+        match self {
+            AoJiaError::Com { call, .. }
+            | AoJiaError::Conversion { call, .. }
+            | AoJiaError::Parse { call, .. }
+            | AoJiaError::Plugin { call, .. }
+            | AoJiaError::InvalidArgument { call, .. }
+            | AoJiaError::Timeout { call, .. }
+            | AoJiaError::Replay { call, .. } => Some(call),
+            AoJiaError::DllLoad { .. }
+            | AoJiaError::WaitTimeout { .. }
+            | AoJiaError::Cancelled { .. } => None,
+        }
+    }
+
+    pub fn hresult(&self) -> Option<i32> {
+        match self {
+            AoJiaError::Com { hresult, .. } => Some(*hresult),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AoJiaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AoJiaError::Com {
+                call,
+                hresult,
+                message,
+            } => write!(f, "{call} 调用失败：{message} (0x{:08X})", *hresult as u32),
+            AoJiaError::DllLoad {
+                a_regj_path,
+                ao_jia_path,
+                reason,
+            } => write!(f, "通过 {a_regj_path} 加载 {ao_jia_path} 失败：{reason}"),
+            AoJiaError::Conversion { call, source } => write!(f, "{call} 的结果{source}"),
+            AoJiaError::Parse { call, source } => write!(f, "{call} 的{source}"),
+            AoJiaError::Plugin { call, code } => write!(f, "{call} 返回失败码 {code}"),
+            AoJiaError::InvalidArgument { call, source } => {
+                write!(f, "{call} 未调用，{source}")
+            }
+            AoJiaError::Timeout { call, timeout } => write!(f, "{call} 超时（{timeout:?}）"),
+            AoJiaError::WaitTimeout {
+                what,
+                polls,
+                elapsed,
+            } => write!(f, "等待{what} 超时：{elapsed:?} 内检查了 {polls} 次"),
+            AoJiaError::Cancelled { what, polls } => {
+                write!(f, "等待{what} 已取消，此前检查了 {polls} 次")
+            }
+            AoJiaError::Replay { call, reason } => write!(f, "重放 {call} 失败：{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for AoJiaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AoJiaError::Conversion { source, .. } => Some(source),
+            AoJiaError::Parse { source, .. } => Some(source),
+            AoJiaError::InvalidArgument { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! 由字体文件生成字库
+//!
+//! 每个字按给定的像素大小光栅化，覆盖率不低于阈值的点视为文字，
+//! 再裁去四周的空白，得到与 [`Dict`] 相同格式的条目。
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ab_glyph::{Font, FontVec, PxScale, VariableFont, point};
+
+use crate::dict::{Bitmap, Dict, DictEntry};
+
+// 关闭抗锯齿时按此倍数超采样，只取每个像素中心的子像素
+const SUPERSAMPLE: u32 = 5;
+
+/// 按字体生成字库的参数
+///
+/// ```no_run
+/// # use aojia::*;
+/// # fn demo() -> std::io::Result<()> {
+/// let dict = FontDict::open("C:/Windows/Fonts/simsun.ttc")?
+///     .size(12.0)
+///     .antialias(false)
+///     .generate("确定取消");
+/// dict.save("dict/simsun12.txt")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FontDict {
+    font: FontVec,
+    size: f32,
+    threshold: f32,
+    antialias: bool,
+    embolden: u32,
+}
+
+impl FontDict {
+    /// 读取字体文件，字体集合取其中第一个字体
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_vec(fs::read(path)?)
+    }
+
+    pub fn from_vec(data: Vec<u8>) -> io::Result<Self> {
+        let font = FontVec::try_from_vec(data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("无效的字体：{e}")))?;
+        Ok(Self {
+            font,
+            size: 12.0,
+            threshold: 0.5,
+            antialias: true,
+            embolden: 0,
+        })
+    }
+
+    /// 字号，即以像素计的行高，默认为 12
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// 覆盖率不低于 `threshold` 的点视为文字，取值 0 到 1，默认为 0.5
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// 是否按抗锯齿的覆盖率取点，默认开启
+    ///
+    /// 关闭时只看像素中心是否落在字形内，与关闭字体平滑的程序画出的字一致。
+    pub fn antialias(mut self, antialias: bool) -> Self {
+        self.antialias = antialias;
+        self
+    }
+
+    /// 可变字体的字重，如 400 为常规、700 为粗体，字体没有字重轴时无效，可改用 [`FontDict::embolden`]
+    pub fn weight(mut self, weight: f32) -> Self {
+        self.font.set_variation(b"wght", weight);
+        self
+    }
+
+    /// 把每个点向右加宽 `pixels` 个像素，模拟粗体
+    pub fn embolden(mut self, pixels: u32) -> Self {
+        self.embolden = pixels;
+        self
+    }
+
+    /// 光栅化单个字，字体中没有该字或该字没有笔画（如空格）时为 `None`
+    pub fn glyph(&self, ch: char) -> Option<Bitmap> {
+        let id = self.font.glyph_id(ch);
+        if id.0 == 0 {
+            return None;
+        }
+        let factor = if self.antialias { 1 } else { SUPERSAMPLE };
+        let scale = PxScale::from(self.size * factor as f32);
+        let outlined = self
+            .font
+            .outline_glyph(id.with_scale_and_position(scale, point(0.0, 0.0)))?;
+        let bounds = outlined.px_bounds();
+        let (min_x, min_y) = (bounds.min.x as i32, bounds.min.y as i32);
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+        let mut coverage = vec![0.0f32; width as usize * height as usize];
+        outlined.draw(|x, y, c| coverage[y as usize * width as usize + x as usize] = c);
+        let sample = |x: i32, y: i32| -> f32 {
+            let (x, y) = (x - min_x, y - min_y);
+            if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                0.0
+            } else {
+                coverage[y as usize * width as usize + x as usize]
+            }
+        };
+
+        // 以原始大小的像素为单位，原点为字形的左上角
+        let f = factor as i32;
+        let (left, top) = (min_x.div_euclid(f), min_y.div_euclid(f));
+        let right = (min_x + width as i32 + f - 1).div_euclid(f);
+        let bottom = (min_y + height as i32 + f - 1).div_euclid(f);
+        let (w, h) = ((right - left) as u32, (bottom - top) as u32);
+        let inked = |x: u32, y: u32| {
+            let (px, py) = (left + x as i32, top + y as i32);
+            sample(px * f + f / 2, py * f + f / 2) >= self.threshold
+        };
+        trim(&Bitmap::from_fn(w + self.embolden, h, |x, y| {
+            (0..=self.embolden.min(x)).any(|d| x - d < w && inked(x - d, y))
+        }))
+    }
+
+    /// 单个字的字库条目
+    pub fn entry(&self, ch: char) -> Option<DictEntry> {
+        self.glyph(ch)
+            .map(|bitmap| DictEntry::new(ch.to_string(), bitmap))
+    }
+
+    /// 为 `chars` 中的每个字生成条目，跳过重复的字及无法生成的字
+    pub fn generate(&self, chars: &str) -> Dict {
+        let mut dict = Dict::new();
+        self.extend(&mut dict, chars);
+        dict
+    }
+
+    /// 把 `chars` 中字库里还没有的字追加到 `dict`，返回追加的个数
+    pub fn extend(&self, dict: &mut Dict, chars: &str) -> usize {
+        let mut added = 0;
+        for ch in chars.chars() {
+            if dict.get(ch.encode_utf8(&mut [0; 4])).is_some() {
+                continue;
+            }
+            if let Some(entry) = self.entry(ch) {
+                dict.push(entry);
+                added += 1;
+            }
+        }
+        added
+    }
+}
+
+impl std::fmt::Debug for FontDict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FontDict")
+            .field("size", &self.size)
+            .field("threshold", &self.threshold)
+            .field("antialias", &self.antialias)
+            .field("embolden", &self.embolden)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 裁去四周的空白，全空时为 `None`
+fn trim(bitmap: &Bitmap) -> Option<Bitmap> {
+    let (w, h) = (bitmap.width(), bitmap.height());
+    let cols: Vec<u32> = (0..w)
+        .filter(|&x| (0..h).any(|y| bitmap.get(x, y)))
+        .collect();
+    let rows: Vec<u32> = (0..h)
+        .filter(|&y| (0..w).any(|x| bitmap.get(x, y)))
+        .collect();
+    let (&x1, &x2) = (cols.first()?, cols.last()?);
+    let (&y1, &y2) = (rows.first()?, rows.last()?);
+    Some(Bitmap::from_fn(x2 - x1 + 1, y2 - y1 + 1, |x, y| {
+        bitmap.get(x1 + x, y1 + y)
+    }))
+}
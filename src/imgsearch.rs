@@ -0,0 +1,443 @@
+//! 纯 Rust 实现的找图，与插件 `FindPic`/`FindPicEx` 的语义一致
+//!
+//! - 区域为 `[x1, x2) × [y1, y2)`，超出图像的部分被裁掉，图片须完整落在区域内；
+//! - 两点各通道之差都不超过 `ColorP` 的对应通道时视为相同；
+//! - 相同的点占参与比较的点的比例不低于 `Sim` 时视为找到；
+//! - `Transparency::Corners` 时，若图片四角颜色相同，与该颜色完全相同的点不参与比较；
+//! - 按 `Dir` 的顺序逐点查找，多张图片时按 `PicName` 中的顺序逐张查找。
+//!
+//! 可用于在 Linux 上以截图验证图片素材及相似度，或在插件无法截图时代替插件找图。
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::color::Rgb;
+use crate::pic::{PicSearch, SearchDir, Transparency};
+use crate::typed::{PicMatch, Rect};
+
+/// 读取或解析图片失败
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    /// 格式不正确或不受支持
+    Format(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "读取图片失败：{e}"),
+            ImageError::Format(reason) => write!(f, "图片格式错误：{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(e) => Some(e),
+            ImageError::Format(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn format_error(reason: impl Into<String>) -> ImageError {
+    ImageError::Format(reason.into())
+}
+
+/// 按行存储的 RGB 图像，原点在左上角
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RgbImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgb>,
+}
+
+impl RgbImage {
+    pub fn filled(width: u32, height: u32, color: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width as usize * height as usize],
+        }
+    }
+
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(u32, u32) -> Rgb) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// 由逐行排列的像素构造，像素个数须为 `width * height`
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Rgb>) -> Result<Self, ImageError> {
+        if pixels.len() != width as usize * height as usize {
+            return Err(format_error(format!(
+                "{width}x{height} 的图像需要 {} 个像素，实际为 {}",
+                width as usize * height as usize,
+                pixels.len()
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 整幅图像的区域
+    pub fn rect(&self) -> Rect {
+        Rect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    pub fn pixels(&self) -> &[Rgb] {
+        &self.pixels
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// 坐标超出图像时 panic
+    pub fn pixel(&self, x: u32, y: u32) -> Rgb {
+        assert!(x < self.width && y < self.height, "({x}, {y}) 超出图像");
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<Rgb> {
+        let (x, y) = (u32::try_from(x).ok()?, u32::try_from(y).ok()?);
+        (x < self.width && y < self.height).then(|| self.pixels[self.index(x, y)])
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: Rgb) {
+        assert!(x < self.width && y < self.height, "({x}, {y}) 超出图像");
+        let i = self.index(x, y);
+        self.pixels[i] = color;
+    }
+
+    /// 把 `image` 画到 `(x, y)`，超出部分被裁掉
+    pub fn draw(&mut self, image: &RgbImage, x: i32, y: i32) {
+        for sy in 0..image.height {
+            for sx in 0..image.width {
+                let (dx, dy) = (x + sx as i32, y + sy as i32);
+                if self.get(dx, dy).is_some() {
+                    self.set(dx as u32, dy as u32, image.pixel(sx, sy));
+                }
+            }
+        }
+    }
+
+    /// 截取区域 `[x1, x2) × [y1, y2)`，超出图像的部分被裁掉
+    pub fn crop(&self, region: Rect) -> RgbImage {
+        let (x1, y1, x2, y2) = self.clip(region);
+        RgbImage::from_fn(x2 - x1, y2 - y1, |x, y| self.pixel(x1 + x, y1 + y))
+    }
+
+    /// 区域与图像的交集，返回 `(x1, y1, x2, y2)`
+    pub(crate) fn clip(&self, region: Rect) -> (u32, u32, u32, u32) {
+        let clamp = |v: i32, max: u32| v.clamp(0, max as i32) as u32;
+        let (x1, x2) = (clamp(region.x1, self.width), clamp(region.x2, self.width));
+        let (y1, y2) = (clamp(region.y1, self.height), clamp(region.y2, self.height));
+        (x1, y1, x2.max(x1), y2.max(y1))
+    }
+
+    /// 读取图片文件，支持 BMP，开启 `png` feature 后支持 PNG
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    /// 按文件头识别格式并解码
+    pub fn decode(bytes: &[u8]) -> Result<Self, ImageError> {
+        if bytes.starts_with(b"BM") {
+            Self::from_bmp(bytes)
+        } else if bytes.starts_with(PNG_SIGNATURE) {
+            #[cfg(feature = "png")]
+            return Self::from_png(bytes);
+            #[cfg(not(feature = "png"))]
+            Err(format_error("读取 PNG 需开启 png feature"))
+        } else {
+            Err(format_error("不支持的图片格式"))
+        }
+    }
+
+    /// 解码未压缩的 24 位或 32 位 BMP
+    pub fn from_bmp(bytes: &[u8]) -> Result<Self, ImageError> {
+        let u16_at = |i: usize| {
+            bytes
+                .get(i..i + 2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .ok_or_else(|| format_error("BMP 文件头不完整"))
+        };
+        let u32_at = |i: usize| {
+            bytes
+                .get(i..i + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or_else(|| format_error("BMP 文件头不完整"))
+        };
+        if !bytes.starts_with(b"BM") {
+            return Err(format_error("不是 BMP 文件"));
+        }
+        let offset = u32_at(10)? as usize;
+        let width = u32_at(18)? as i32;
+        let height = u32_at(22)? as i32;
+        let bits = u16_at(28)?;
+        let compression = u32_at(30)?;
+        // BI_RGB，或 32 位时的 BI_BITFIELDS（按默认的 BGRA 掩码处理）
+        if !(compression == 0 || compression == 3 && bits == 32) {
+            return Err(format_error(format!("不支持压缩方式 {compression}")));
+        }
+        if bits != 24 && bits != 32 {
+            return Err(format_error(format!("不支持 {bits} 位色深")));
+        }
+        if width <= 0 || height == 0 {
+            return Err(format_error(format!("无效的尺寸 {width}x{height}")));
+        }
+
+        // 高度为负时自上而下存储
+        let top_down = height < 0;
+        let (width, height) = (width as u32, height.unsigned_abs());
+        let bytes_per_pixel = bits as usize / 8;
+        // 尺寸来自文件头，计算时不能溢出
+        let too_large = || format_error(format!("尺寸 {width}x{height} 过大"));
+        let stride = (width as usize)
+            .checked_mul(bytes_per_pixel)
+            .map(|row| row.div_ceil(4) * 4)
+            .ok_or_else(too_large)?;
+        let needed = stride
+            .checked_mul(height as usize)
+            .and_then(|data| data.checked_add(offset))
+            .ok_or_else(too_large)?;
+        if bytes.len() < needed {
+            return Err(format_error(format!(
+                "像素数据不完整，需要 {needed} 字节，实际为 {}",
+                bytes.len()
+            )));
+        }
+
+        Ok(Self::from_fn(width, height, |x, y| {
+            let row = if top_down { y } else { height - 1 - y };
+            let i = offset + row as usize * stride + x as usize * bytes_per_pixel;
+            Rgb::new(bytes[i + 2], bytes[i + 1], bytes[i])
+        }))
+    }
+
+    /// 编码为 24 位 BMP
+    pub fn to_bmp(&self) -> Vec<u8> {
+        let stride = (self.width as usize * 3).div_ceil(4) * 4;
+        let data_size = stride * self.height as usize;
+        let mut out = Vec::with_capacity(54 + data_size);
+        out.extend_from_slice(b"BM");
+        out.extend_from_slice(&(54 + data_size as u32).to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&54u32.to_le_bytes());
+        out.extend_from_slice(&40u32.to_le_bytes());
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.height.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&24u16.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&(data_size as u32).to_le_bytes());
+        out.extend_from_slice(&[0; 16]);
+        for y in (0..self.height).rev() {
+            let start = out.len();
+            for x in 0..self.width {
+                let c = self.pixel(x, y);
+                out.extend_from_slice(&[c.b, c.g, c.r]);
+            }
+            out.resize(start + stride, 0);
+        }
+        out
+    }
+
+    pub fn save_bmp(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bmp())
+    }
+
+    /// 解码 PNG，带透明通道时忽略透明度
+    #[cfg(feature = "png")]
+    pub fn from_png(bytes: &[u8]) -> Result<Self, ImageError> {
+        let png_error = |e: png::DecodingError| format_error(format!("PNG 解码失败：{e}"));
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(png_error)?;
+        let channels = info.color_type.samples();
+        let (width, height) = (info.width, info.height);
+        Ok(Self::from_fn(width, height, |x, y| {
+            let i = y as usize * info.line_size + x as usize * channels;
+            match info.color_type {
+                png::ColorType::Grayscale | png::ColorType::GrayscaleAlpha => {
+                    Rgb::new(buf[i], buf[i], buf[i])
+                }
+                _ => Rgb::new(buf[i], buf[i + 1], buf[i + 2]),
+            }
+        }))
+    }
+
+    /// 编码为 8 位 RGB 的 PNG，PNG 不允许空图像，宽或高为 0 时 panic
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = self.pixels.iter().flat_map(|c| [c.r, c.g, c.b]).collect();
+        // 写入内存不会产生 IO 错误，数据长度也总与尺寸一致
+        let mut writer = encoder.write_header().expect("PNG 文件头写入内存");
+        writer.write_image_data(&data).expect("PNG 数据写入内存");
+        writer.finish().expect("PNG 写入内存");
+        out
+    }
+
+    #[cfg(feature = "png")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_png())
+    }
+}
+
+/// 图片的透明色：四角颜色相同时为该颜色
+pub fn transparent_color(pic: &RgbImage, transparency: Transparency) -> Option<Rgb> {
+    if transparency == Transparency::Opaque || pic.width == 0 || pic.height == 0 {
+        return None;
+    }
+    let (w, h) = (pic.width - 1, pic.height - 1);
+    let corner = pic.pixel(0, 0);
+    [pic.pixel(w, 0), pic.pixel(0, h), pic.pixel(w, h)]
+        .iter()
+        .all(|&c| c == corner)
+        .then_some(corner)
+}
+
+/// 图片左上角放在 `(x, y)` 时与屏幕相同的点所占的比例，图片超出屏幕时为 0
+pub fn similarity_at(
+    screen: &RgbImage,
+    pic: &RgbImage,
+    x: i32,
+    y: i32,
+    delta: Rgb,
+    transparency: Transparency,
+) -> f64 {
+    let transparent = transparent_color(pic, transparency);
+    let (mut total, mut same) = (0u64, 0u64);
+    for py in 0..pic.height {
+        for px in 0..pic.width {
+            let c = pic.pixel(px, py);
+            if Some(c) == transparent {
+                continue;
+            }
+            total += 1;
+            match screen.get(x + px as i32, y + py as i32) {
+                Some(s) if s.within(c, delta) => same += 1,
+                Some(_) => {}
+                None => return 0.0,
+            }
+        }
+    }
+    if total == 0 {
+        1.0
+    } else {
+        same as f64 / total as f64
+    }
+}
+
+/// 在一张图片上查找，`found` 返回 `false` 时停止
+fn scan(
+    screen: &RgbImage,
+    pic: &RgbImage,
+    search: &PicSearch,
+    mut found: impl FnMut(u32, u32) -> bool,
+) {
+    let (x1, y1, x2, y2) = screen.clip(search.region);
+    if pic.width == 0 || pic.height == 0 || x2 - x1 < pic.width || y2 - y1 < pic.height {
+        return;
+    }
+    let transparent = transparent_color(pic, search.transparency);
+    let points: Vec<(u32, u32, Rgb)> = (0..pic.height)
+        .flat_map(|y| (0..pic.width).map(move |x| (x, y)))
+        .map(|(x, y)| (x, y, pic.pixel(x, y)))
+        .filter(|&(_, _, c)| Some(c) != transparent)
+        .collect();
+    // 允许不同的点数，加上微小量以免 0.9 之类的相似度因浮点误差少算一点
+    let sim = search.sim.clamp(0.0, 1.0);
+    let allowed = ((1.0 - sim) * points.len() as f64 + 1e-9).floor() as usize;
+
+    let xs = x1..=x2 - pic.width;
+    let ys = y1..=y2 - pic.height;
+    let (rows, cols): (Vec<u32>, Vec<u32>) = match search.dir {
+        SearchDir::LeftTop => (ys.collect(), xs.collect()),
+        SearchDir::LeftBottom => (ys.rev().collect(), xs.collect()),
+        SearchDir::RightTop => (ys.collect(), xs.rev().collect()),
+        SearchDir::RightBottom => (ys.rev().collect(), xs.rev().collect()),
+    };
+    for &y in &rows {
+        for &x in &cols {
+            let mut misses = 0;
+            let matched = points.iter().all(|&(px, py, c)| {
+                if !screen.pixel(x + px, y + py).within(c, search.delta) {
+                    misses += 1;
+                }
+                misses <= allowed
+            });
+            if matched && !found(x, y) {
+                return;
+            }
+        }
+    }
+}
+
+fn pic_match(search: &PicSearch, index: usize, x: u32, y: u32) -> PicMatch {
+    PicMatch {
+        index,
+        name: search.names.get(index).cloned().unwrap_or_default(),
+        x: x as i32,
+        y: y as i32,
+    }
+}
+
+/// 对应 `FindPic`，`pics` 与 `search.names` 一一对应
+pub fn find_pic(screen: &RgbImage, pics: &[RgbImage], search: &PicSearch) -> Option<PicMatch> {
+    pics.iter().enumerate().find_map(|(index, pic)| {
+        let mut first = None;
+        scan(screen, pic, search, |x, y| {
+            first = Some(pic_match(search, index, x, y));
+            false
+        });
+        first
+    })
+}
+
+/// 对应 `FindPicEx`，返回每张图片所有相符的位置，位置之间可能重叠
+pub fn find_pic_ex(screen: &RgbImage, pics: &[RgbImage], search: &PicSearch) -> Vec<PicMatch> {
+    let mut matches = Vec::new();
+    for (index, pic) in pics.iter().enumerate() {
+        scan(screen, pic, search, |x, y| {
+            matches.push(pic_match(search, index, x, y));
+            true
+        });
+    }
+    matches
+}
@@ -0,0 +1,347 @@
+//! 调用日志，每次调用追加一行 JSON
+//!
+//! ```text
+//! {"ts":1700000000123,"method":"FindPic","args":[0,0,800,600,"a.bmp","000000",0.9,0,0],"outs":["a.bmp",10,20],"ret":0,"hresult":0,"duration_us":1520}
+//! ```
+//!
+//! `args` 只含传值参数，`outs` 为调用后的传址参数；调用失败时 `ret` 为 `null`，
+//! 并记录 `hresult`（非 COM 错误为 0）、`error` 及错误种类 `kind`。
+
+use std::collections::BTreeSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dispatch::{Arg, Call, Dispatch, Value};
+use crate::error::{AoJiaError, ConversionError, ParseError, Result};
+
+/// 日志中的一行
+///
+/// 值以 JSON 记录：整数读回时为 `I32`（超出范围时为 `I64`），
+/// NaN 及无穷大等非有限浮点数记为 `null`，读回时为 `Value::Empty`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Unix 时间戳，毫秒
+    pub ts: u64,
+    pub method: String,
+    /// 传值参数
+    pub args: Vec<Value>,
+    /// 调用后的传址参数
+    pub outs: Vec<Value>,
+    pub ret: Value,
+    /// 成功时为 0
+    pub hresult: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 错误种类，旧日志中没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<JournalError>,
+    pub duration_us: u64,
+}
+
+/// 日志中记录的错误种类及其内容，重放时据此还原同样的 [`AoJiaError`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalError {
+    /// 错误信息及 HRESULT 记录在条目中
+    Com,
+    Conversion {
+        value: String,
+        target: String,
+    },
+    Parse {
+        what: String,
+        input: String,
+        reason: String,
+    },
+    Plugin {
+        code: i64,
+    },
+    InvalidArgument {
+        what: String,
+        input: String,
+        reason: String,
+    },
+    Timeout {
+        timeout_us: u64,
+    },
+    Replay {
+        reason: String,
+    },
+}
+
+impl JournalError {
+    /// 没有对应调用的错误不会由后端返回，记为 `None`
+    fn of(e: &AoJiaError) -> Option<Self> {
+        Some(match e {
+            AoJiaError::Com { .. } => JournalError::Com,
+            AoJiaError::Conversion { source, .. } => JournalError::Conversion {
+                value: source.value.clone(),
+                target: source.target.to_string(),
+            },
+            AoJiaError::Parse { source, .. } => JournalError::Parse {
+                what: source.what.to_string(),
+                input: source.input.clone(),
+                reason: source.reason.clone(),
+            },
+            AoJiaError::Plugin { code, .. } => JournalError::Plugin { code: *code },
+            AoJiaError::InvalidArgument { source, .. } => JournalError::InvalidArgument {
+                what: source.what.to_string(),
+                input: source.input.clone(),
+                reason: source.reason.clone(),
+            },
+            AoJiaError::Timeout { timeout, .. } => JournalError::Timeout {
+                timeout_us: timeout.as_micros() as u64,
+            },
+            AoJiaError::Replay { reason, .. } => JournalError::Replay {
+                reason: reason.clone(),
+            },
+            _ => return None,
+        })
+    }
+
+    /// 还原为 `call` 的错误，COM 错误使用条目中的 `hresult` 及 `message`
+    ///
+    /// `what`、`target` 等在 `AoJiaError` 中为 `&'static str`，每种文本只分配一次且不释放。
+    pub fn into_error(self, call: Call, hresult: i32, message: String) -> AoJiaError {
+        match self {
+            JournalError::Com => AoJiaError::com(call, hresult, message),
+            JournalError::Conversion { value, target } => AoJiaError::Conversion {
+                call,
+                source: ConversionError::new(value, intern(target)),
+            },
+            JournalError::Parse {
+                what,
+                input,
+                reason,
+            } => AoJiaError::Parse {
+                call,
+                source: ParseError::new(intern(what), &input, reason),
+            },
+            JournalError::Plugin { code } => AoJiaError::Plugin { call, code },
+            JournalError::InvalidArgument {
+                what,
+                input,
+                reason,
+            } => AoJiaError::InvalidArgument {
+                call,
+                source: ParseError::new(intern(what), &input, reason),
+            },
+            JournalError::Timeout { timeout_us } => AoJiaError::Timeout {
+                call,
+                timeout: Duration::from_micros(timeout_us),
+            },
+            JournalError::Replay { reason } => AoJiaError::Replay { call, reason },
+        }
+    }
+}
+
+fn intern(text: String) -> &'static str {
+    static INTERNED: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&text) = interned.get(text.as_str()) {
+        return text;
+    }
+    let text: &'static str = Box::leak(text.into_boxed_str());
+    interned.insert(text);
+    text
+}
+
+/// 遮盖敏感参数的函数，参数依次为方法名、参数位置及参数值，返回 `Some` 时以其代替原值
+///
+/// 传值参数与传址参数都会经过该函数，参数位置按插件文档的参数顺序计算。
+pub type Redact = Box<dyn Fn(&str, usize, &Value) -> Option<Value> + Send + Sync>;
+
+struct Output {
+    file: File,
+    size: u64,
+    error: Option<io::Error>,
+}
+
+/// 记录每次调用的后端包装
+///
+/// ```no_run
+/// # use aojia::*;
+/// # fn demo(backend: MockAoJia) -> std::io::Result<()> {
+/// let journal = Journal::open(backend, "aojia.jsonl")?
+///     .rotate(10 << 20, 3)
+///     .redact(|method, _, value| (method == "SetPath").then(|| Value::from("***")));
+/// let aojia = AoJia::with_backend(journal);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Journal<B> {
+    backend: B,
+    path: PathBuf,
+    output: Mutex<Output>,
+    /// 单个文件的最大字节数及保留的旧文件个数
+    rotation: Option<(u64, usize)>,
+    redact: Option<Redact>,
+}
+
+impl<B> Journal<B> {
+    /// 以追加方式打开日志文件
+    pub fn open(backend: B, path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            backend,
+            path,
+            output: Mutex::new(Output {
+                file,
+                size,
+                error: None,
+            }),
+            rotation: None,
+            redact: None,
+        })
+    }
+
+    /// 文件超过 `max_bytes` 时改名为 `<path>.1`，原有的 `.1` 改为 `.2`，依此类推，最多保留 `keep` 个
+    pub fn rotate(mut self, max_bytes: u64, keep: usize) -> Self {
+        self.rotation = Some((max_bytes, keep));
+        self
+    }
+
+    pub fn redact(
+        mut self,
+        redact: impl Fn(&str, usize, &Value) -> Option<Value> + Send + Sync + 'static,
+    ) -> Self {
+        self.redact = Some(Box::new(redact));
+        self
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 取出最近一次写日志失败的错误，写日志失败不影响调用本身
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.output().error.take()
+    }
+
+    fn output(&self) -> std::sync::MutexGuard<'_, Output> {
+        self.output.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn write(&self, entry: &JournalEntry) {
+        let mut line = serde_json::to_string(entry).expect("日志条目总能序列化");
+        line.push('\n');
+        let mut output = self.output();
+        if let Err(e) = self.write_line(&mut output, line.as_bytes()) {
+            output.error = Some(e);
+        }
+    }
+
+    fn write_line(&self, output: &mut Output, line: &[u8]) -> io::Result<()> {
+        if let Some((max_bytes, keep)) = self.rotation
+            && output.size > 0
+            && output.size + line.len() as u64 > max_bytes
+        {
+            for n in (1..keep).rev() {
+                let from = self.rotated(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated(n + 1))?;
+                }
+            }
+            if keep > 0 {
+                fs::rename(&self.path, self.rotated(1))?;
+            }
+            output.file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?;
+            output.size = 0;
+        }
+        output.file.write_all(line)?;
+        output.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+// 错误信息中的调用参数未经遮盖，因此只记录错误本身
+fn error_message(e: &AoJiaError) -> String {
+    match e {
+        AoJiaError::Com { message, .. } => message.clone(),
+        AoJiaError::Conversion { source, .. } => source.to_string(),
+        other => other.to_string(),
+    }
+}
+
+impl<B: Dispatch> Dispatch for Journal<B> {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let redacted = |args: &[Arg], out: bool| -> Vec<Value> {
+            args.iter()
+                .enumerate()
+                .filter(|(_, arg)| arg.is_out() == out)
+                .map(|(i, arg)| {
+                    let value = arg.value();
+                    self.redact
+                        .as_ref()
+                        .and_then(|redact| redact(name, i, value))
+                        .unwrap_or_else(|| value.clone())
+                })
+                .collect()
+        };
+        let inputs = redacted(args, false);
+
+        let start = Instant::now();
+        let result = self.backend.invoke(name, args);
+        let duration_us = start.elapsed().as_micros() as u64;
+
+        let (ret, hresult, error, kind) = match &result {
+            Ok(ret) => (ret.clone(), 0, None, None),
+            Err(e) => (
+                Value::Empty,
+                e.hresult().unwrap_or(0),
+                Some(error_message(e)),
+                JournalError::of(e),
+            ),
+        };
+        self.write(&JournalEntry {
+            ts,
+            method: name.to_string(),
+            args: inputs,
+            outs: redacted(args, true),
+            ret,
+            hresult,
+            error,
+            kind,
+            duration_us,
+        });
+        result
+    }
+
+    fn resolve(&self, name: &str) -> Result<()> {
+        self.backend.resolve(name)
+    }
+}
+
+impl<B: std::fmt::Debug> std::fmt::Debug for Journal<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Journal")
+            .field("backend", &self.backend)
+            .field("path", &self.path)
+            .field("rotation", &self.rotation)
+            .finish_non_exhaustive()
+    }
+}
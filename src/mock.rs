@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::dispatch::{Arg, Call, Dispatch, Value};
+use crate::error::{AoJiaError, Result};
+
+/// 参数个数不符，对应 DISP_E_BADPARAMCOUNT
+const DISP_E_BADPARAMCOUNT: i32 = 0x8002_000E_u32 as i32;
+/// 未知的方法名，对应 DISP_E_UNKNOWNNAME
+const DISP_E_UNKNOWNNAME: i32 = 0x8002_0006_u32 as i32;
+/// 没有预设应答，对应 E_UNEXPECTED
+const E_UNEXPECTED: i32 = 0x8000_FFFF_u32 as i32;
+
+/// 预设的应答
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    /// 返回值及依次写回传址参数的值
+    Ok { ret: Value, outs: Vec<Value> },
+    /// 以 COM 错误失败
+    Fail { hresult: i32, message: String },
+}
+
+impl Reply {
+    pub fn ret(ret: impl Into<Value>) -> Self {
+        Reply::Ok {
+            ret: ret.into(),
+            outs: Vec::new(),
+        }
+    }
+
+    pub fn with_outs<V: Into<Value>>(
+        ret: impl Into<Value>,
+        outs: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Reply::Ok {
+            ret: ret.into(),
+            outs: outs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    calls: Vec<Call>,
+    queued: HashMap<String, VecDeque<Reply>>,
+    defaults: HashMap<String, Reply>,
+    missing: HashSet<String>,
+}
+
+/// 记录所有调用并按预设应答的测试后端
+///
+/// 每个方法名各有一个应答队列，队列为空时使用 [`MockAoJia::set_default`]
+/// 设置的应答，二者都没有时以 E_UNEXPECTED 失败，以免未预设的调用被当作成功。
+/// 需要空返回值时显式设置 `Reply::ret(Value::Empty)`。
+#[derive(Debug, Default)]
+pub struct MockAoJia {
+    state: Mutex<State>,
+}
+
+impl MockAoJia {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 为 `name` 追加一次应答
+    pub fn push(&self, name: &str, reply: Reply) -> &Self {
+        self.state()
+            .queued
+            .entry(name.to_string())
+            .or_default()
+            .push_back(reply);
+        self
+    }
+
+    pub fn push_return(&self, name: &str, ret: impl Into<Value>) -> &Self {
+        self.push(name, Reply::ret(ret))
+    }
+
+    pub fn push_outs<V: Into<Value>>(
+        &self,
+        name: &str,
+        ret: impl Into<Value>,
+        outs: impl IntoIterator<Item = V>,
+    ) -> &Self {
+        self.push(name, Reply::with_outs(ret, outs))
+    }
+
+    pub fn push_error(&self, name: &str, hresult: i32, message: &str) -> &Self {
+        self.push(
+            name,
+            Reply::Fail {
+                hresult,
+                message: message.to_string(),
+            },
+        )
+    }
+
+    /// 队列耗尽后 `name` 的默认应答
+    pub fn set_default(&self, name: &str, reply: Reply) -> &Self {
+        self.state().defaults.insert(name.to_string(), reply);
+        self
+    }
+
+    /// 模拟插件中不存在的方法，如免费版中的收费函数
+    pub fn set_missing(&self, name: &str) -> &Self {
+        self.state().missing.insert(name.to_string());
+        self
+    }
+
+    /// 尚未被消耗的应答数量
+    pub fn pending(&self) -> usize {
+        self.state().queued.values().map(VecDeque::len).sum()
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.state().calls.clone()
+    }
+
+    pub fn calls_to(&self, name: &str) -> Vec<Call> {
+        self.state()
+            .calls
+            .iter()
+            .filter(|c| c.name == name)
+            .cloned()
+            .collect()
+    }
+
+    /// 按顺序列出被调用的方法名
+    pub fn call_names(&self) -> Vec<String> {
+        self.state().calls.iter().map(|c| c.name.clone()).collect()
+    }
+
+    pub fn take_calls(&self) -> Vec<Call> {
+        std::mem::take(&mut self.state().calls)
+    }
+}
+
+impl Dispatch for MockAoJia {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        let mut state = self.state();
+        let call = Call::new(name, args);
+        state.calls.push(call.clone());
+        if state.missing.contains(name) {
+            return Err(AoJiaError::com(call, DISP_E_UNKNOWNNAME, "未知名称"));
+        }
+
+        let reply = state
+            .queued
+            .get_mut(name)
+            .and_then(VecDeque::pop_front)
+            .or_else(|| state.defaults.get(name).cloned());
+
+        match reply {
+            None => Err(AoJiaError::com(
+                call,
+                E_UNEXPECTED,
+                format!("{name} 没有预设应答"),
+            )),
+            Some(Reply::Fail { hresult, message }) => Err(AoJiaError::com(call, hresult, message)),
+            Some(Reply::Ok { ret, outs }) => {
+                let mut slots = args.iter_mut().filter(|a| a.is_out());
+                for value in outs {
+                    match slots.next() {
+                        Some(slot) => *slot = Arg::Out(value),
+                        None => {
+                            return Err(AoJiaError::com(
+                                call,
+                                DISP_E_BADPARAMCOUNT,
+                                "传址参数少于预设的回写值",
+                            ));
+                        }
+                    }
+                }
+                Ok(ret)
+            }
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Result<()> {
+        if self.state().missing.contains(name) {
+            return Err(AoJiaError::com(
+                Call::new(name, &[]),
+                DISP_E_UNKNOWNNAME,
+                "未知名称",
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+//! `Ocr` 的参数构造及结果解析
+//!
+//! 插件的输出由 `TypeR` 与 `TypeT` 决定：
+//!
+//! - 只返回文字时为识别出的文本；
+//! - 返回坐标时每个字为 `字,x,y`，字与字之间以 `|` 分隔；字本身含 `|` 时，
+//!   其中 `|` 之前的部分不能含逗号，否则无法与分隔符区分；
+//! - 分行识别时行与行之间以 `HLine` 分隔，`HLine` 为空时以换行分隔。
+
+use crate::AoJia;
+use crate::bindings::{self, Args};
+use crate::color::ColorSpec;
+use crate::dispatch::{Arg, Call, Dispatch};
+use crate::error::{AoJiaError, ParseError, Result};
+use crate::typed::{Point, Rect};
+
+const WHAT: &str = "Ocr 结果";
+
+/// `Ocr` 的 `TypeC` 参数，决定 `Color` 的含义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OcrColorType {
+    /// `Color` 为文字颜色
+    #[default]
+    Text = 0,
+    /// `Color` 为背景色，非背景色的点视为文字
+    Background = 1,
+}
+
+impl OcrColorType {
+    pub fn type_c(self) -> i32 {
+        self as i32
+    }
+}
+
+try_from_i32!(OcrColorType, "TypeC" { Text, Background });
+
+/// `Ocr` 的 `TypeD` 参数，决定使用哪些字库
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OcrDictScope {
+    /// `SetDict` 选定的字库
+    #[default]
+    Current = 0,
+    /// 所有已加载的字库
+    All = 1,
+}
+
+impl OcrDictScope {
+    pub fn type_d(self) -> i32 {
+        self as i32
+    }
+}
+
+try_from_i32!(OcrDictScope, "TypeD" { Current, All });
+
+/// `Ocr` 的 `TypeR` 参数，决定返回的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OcrOutput {
+    /// 只返回文字
+    #[default]
+    Text = 0,
+    /// 返回每个字及其坐标
+    Positions = 1,
+}
+
+impl OcrOutput {
+    pub fn type_r(self) -> i32 {
+        self as i32
+    }
+}
+
+try_from_i32!(OcrOutput, "TypeR" { Text, Positions });
+
+/// `Ocr` 的 `TypeT` 参数，决定是否分行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OcrLines {
+    /// 区域内的文字视为一行
+    #[default]
+    Single = 0,
+    /// 分行识别，行与行之间以 `HLine` 分隔
+    Multi = 1,
+}
+
+impl OcrLines {
+    pub fn type_t(self) -> i32 {
+        self as i32
+    }
+}
+
+try_from_i32!(OcrLines, "TypeT" { Single, Multi });
+
+/// `Ocr` 的参数，未设置的参数取插件文档的默认值
+///
+/// ```no_run
+/// # use aojia::*;
+/// # fn demo(aojia: &AoJia<MockAoJia>) -> Result<()> {
+/// let color: ColorSpec = "FFFFFF-101010".parse().unwrap();
+/// let result = OcrRequest::new(Rect::new(0, 0, 800, 600), color)
+///     .sim(0.8)
+///     .output(OcrOutput::Positions)
+///     .run(aojia)?;
+/// if let Some(pos) = result.find_text("确定") {
+///     aojia.MoveTo(pos.x, pos.y)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcrRequest {
+    pub region: Rect,
+    /// 只识别其中的字，为空时识别字库中所有的字
+    pub text: String,
+    pub color: ColorSpec,
+    pub sim: f64,
+    pub color_type: OcrColorType,
+    pub dict_scope: OcrDictScope,
+    pub output: OcrOutput,
+    pub lines: OcrLines,
+    /// 多行识别时行与行之间的分隔符
+    pub line_separator: String,
+    /// 在图片中识字，为空时识别屏幕
+    pub pic_name: String,
+}
+
+impl OcrRequest {
+    pub fn new(region: Rect, color: impl Into<ColorSpec>) -> Self {
+        Self {
+            region,
+            text: String::new(),
+            color: color.into(),
+            sim: 0.9,
+            color_type: OcrColorType::default(),
+            dict_scope: OcrDictScope::default(),
+            output: OcrOutput::default(),
+            lines: OcrLines::default(),
+            line_separator: String::new(),
+            pic_name: String::new(),
+        }
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    pub fn sim(mut self, sim: f64) -> Self {
+        self.sim = sim;
+        self
+    }
+
+    pub fn color_type(mut self, color_type: OcrColorType) -> Self {
+        self.color_type = color_type;
+        self
+    }
+
+    pub fn dict_scope(mut self, dict_scope: OcrDictScope) -> Self {
+        self.dict_scope = dict_scope;
+        self
+    }
+
+    pub fn output(mut self, output: OcrOutput) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn lines(mut self, lines: OcrLines) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    pub fn line_separator(mut self, separator: impl Into<String>) -> Self {
+        self.line_separator = separator.into();
+        self
+    }
+
+    pub fn pic_name(mut self, pic_name: impl Into<String>) -> Self {
+        self.pic_name = pic_name.into();
+        self
+    }
+
+    /// 按插件参数顺序排列的参数
+    pub fn args(&self) -> Vec<Arg> {
+        let Rect { x1, y1, x2, y2 } = self.region;
+        Args::Ocr(
+            x1,
+            y1,
+            x2,
+            y2,
+            &self.text,
+            &self.color,
+            self.sim,
+            self.color_type.type_c(),
+            self.dict_scope.type_d(),
+            self.output.type_r(),
+            self.lines.type_t(),
+            &self.line_separator,
+            &self.pic_name,
+        )
+    }
+
+    /// 对应的 `Ocr` 调用，用于日志或比对
+    pub fn call(&self) -> Call {
+        Call::new("Ocr", &self.args())
+    }
+
+    /// 执行识字并解析结果
+    pub fn run<B: Dispatch>(&self, aojia: &AoJia<B>) -> Result<OcrResult> {
+        let raw = self.run_raw(aojia)?;
+        self.parse(&raw).map_err(|source| AoJiaError::Parse {
+            call: self.call(),
+            source,
+        })
+    }
+
+    /// 按本次请求的 `TypeR`、`TypeT` 及 `HLine` 解析插件输出
+    pub fn parse(&self, raw: &str) -> std::result::Result<OcrResult, ParseError> {
+        parse_ocr(raw, self.output, self.lines, &self.line_separator)
+    }
+
+    /// 执行识字，返回插件输出的原始文本
+    pub fn run_raw<B: Dispatch>(&self, aojia: &AoJia<B>) -> Result<String> {
+        bindings::call(aojia, "Ocr", &mut self.args())
+    }
+}
+
+/// 识别出的一个字及其坐标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// 字库中的字，字库允许以多个字符作为一个字
+    pub ch: String,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Glyph {
+    pub fn point(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+}
+
+/// 识别出的一行
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OcrLine {
+    pub text: String,
+    /// 只返回文字时为空
+    pub glyphs: Vec<Glyph>,
+}
+
+impl OcrLine {
+    /// 由字构造，`text` 为各字相连
+    pub fn from_glyphs(glyphs: Vec<Glyph>) -> Self {
+        Self {
+            text: glyphs.iter().map(|g| g.ch.as_str()).collect(),
+            glyphs,
+        }
+    }
+
+    /// 文本在本行中第一次出现时首个字的坐标
+    pub fn find_text(&self, text: &str) -> Option<Point> {
+        if text.is_empty() {
+            return None;
+        }
+        let mut offset = 0;
+        let starts: Vec<usize> = self
+            .glyphs
+            .iter()
+            .map(|g| {
+                let start = offset;
+                offset += g.ch.len();
+                start
+            })
+            .collect();
+        // 只在字的边界上匹配，避免从某个多字符字的中间开始
+        starts
+            .iter()
+            .position(|&start| self.text.get(start..).is_some_and(|s| s.starts_with(text)))
+            .map(|i| self.glyphs[i].point())
+    }
+}
+
+/// `Ocr` 的结果
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OcrResult {
+    pub lines: Vec<OcrLine>,
+}
+
+impl OcrResult {
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| line.text.is_empty())
+    }
+
+    /// 所有行的文本，以换行相连
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn glyphs(&self) -> impl Iterator<Item = &Glyph> {
+        self.lines.iter().flat_map(|line| &line.glyphs)
+    }
+
+    /// 文本第一次出现时首个字的坐标，文本不能跨行；只返回文字时总是 `None`
+    pub fn find_text(&self, text: &str) -> Option<Point> {
+        self.lines.iter().find_map(|line| line.find_text(text))
+    }
+}
+
+fn split_lines<'a>(raw: &'a str, separator: &'a str) -> Vec<&'a str> {
+    if separator.is_empty() {
+        raw.lines().collect()
+    } else {
+        raw.split(separator).collect()
+    }
+}
+
+fn parse_glyphs(line: &str, raw: &str) -> std::result::Result<Vec<Glyph>, ParseError> {
+    if line.is_empty() {
+        return Ok(Vec::new());
+    }
+    let err = |reason: String| ParseError::new(WHAT, raw, reason);
+    let mut glyphs = Vec::new();
+    let mut pieces = line.split('|');
+    while let Some(mut item) = pieces.next().map(str::to_string) {
+        // 没有逗号的片段不是完整的一项，只能是含 `|` 的字（如 `|,1,2`）的前半部分
+        while !item.contains(',') {
+            let Some(next) = pieces.next() else {
+                return Err(err(format!("{item:?} 应为 字,x,y")));
+            };
+            item.push('|');
+            item.push_str(next);
+        }
+        // 字本身可能是逗号，因此从右侧拆分坐标
+        let mut fields = item.rsplitn(3, ',');
+        let (Some(y), Some(x), Some(ch)) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(err(format!("{item:?} 应为 字,x,y")));
+        };
+        let coord = |s: &str| {
+            s.trim()
+                .parse::<i32>()
+                .map_err(|_| err(format!("{s:?} 不是有效的坐标")))
+        };
+        if ch.is_empty() {
+            return Err(err(format!("{item:?} 缺少文字")));
+        }
+        glyphs.push(Glyph {
+            ch: ch.to_string(),
+            x: coord(x)?,
+            y: coord(y)?,
+        });
+    }
+    Ok(glyphs)
+}
+
+/// 解析 `Ocr` 的返回值，参数与调用时一致
+pub fn parse_ocr(
+    raw: &str,
+    output: OcrOutput,
+    lines: OcrLines,
+    line_separator: &str,
+) -> std::result::Result<OcrResult, ParseError> {
+    if raw.is_empty() {
+        return Ok(OcrResult::default());
+    }
+    let raw_lines = match lines {
+        OcrLines::Single => vec![raw],
+        OcrLines::Multi => split_lines(raw, line_separator),
+    };
+    let lines = raw_lines
+        .into_iter()
+        .map(|line| match output {
+            OcrOutput::Text => Ok(OcrLine {
+                text: line.to_string(),
+                glyphs: Vec::new(),
+            }),
+            OcrOutput::Positions => parse_glyphs(line, raw).map(OcrLine::from_glyphs),
+        })
+        .collect::<std::result::Result<_, _>>()?;
+    Ok(OcrResult { lines })
+}
+
+/// 按插件格式生成 `Ocr` 的返回值，与 [`parse_ocr`] 互逆
+pub fn format_ocr(
+    result: &OcrResult,
+    output: OcrOutput,
+    lines: OcrLines,
+    line_separator: &str,
+) -> String {
+    let rendered: Vec<String> = result
+        .lines
+        .iter()
+        .map(|line| match output {
+            OcrOutput::Text => line.text.clone(),
+            OcrOutput::Positions => line
+                .glyphs
+                .iter()
+                .map(|g| format!("{},{},{}", g.ch, g.x, g.y))
+                .collect::<Vec<_>>()
+                .join("|"),
+        })
+        .collect();
+    let separator = match (lines, output) {
+        (OcrLines::Multi, _) if line_separator.is_empty() => "\n",
+        (OcrLines::Multi, _) => line_separator,
+        (OcrLines::Single, OcrOutput::Positions) => "|",
+        (OcrLines::Single, OcrOutput::Text) => "",
+    };
+    rendered.join(separator)
+}
+
+impl<B: Dispatch> AoJia<B> {
+    /// `Ocr` 的配套方法，见 [`OcrRequest`]
+    pub fn ocr(&self, request: &OcrRequest) -> Result<OcrResult> {
+        request.run(self)
+    }
+}
@@ -0,0 +1,270 @@
+//! 纯 Rust 实现的识字，使用与 `LoadDict` 相同的字库
+//!
+//! - 区域的裁剪与找图相同，`Color` 按 `TypeC` 决定哪些点是文字；
+//! - 字库中的字放在某处时，点阵与区域内文字的点不一致的个数
+//!   不超过 `(1 - Sim) × 该字的点数` 即为候选；
+//! - 候选按吻合的点数从多到少依次采用，与已采用的字重叠的候选被丢弃；
+//! - 坐标为字的左上角，单行时按 x 排序，分行时纵向重叠的字归为一行。
+//!
+//! 可用于在 Linux 上检验字库及识字区域，或对照插件的识字结果查找原因。
+
+use std::collections::BTreeMap;
+
+use crate::dict::{Dict, DictEntry};
+use crate::imgsearch::RgbImage;
+use crate::ocr::{Glyph, OcrColorType, OcrDictScope, OcrLine, OcrLines, OcrRequest, OcrResult};
+
+/// 已加载的字库及当前字库，对应插件的 `LoadDict` 与 `SetDict`
+#[derive(Debug, Clone, Default)]
+pub struct OcrEngine {
+    dicts: BTreeMap<i32, Dict>,
+    current: i32,
+}
+
+/// 区域内文字的点
+struct InkMap {
+    width: u32,
+    height: u32,
+    ink: Vec<bool>,
+    /// 二维前缀和，用于快速排除点数相差过多的位置
+    sums: Vec<u32>,
+}
+
+impl InkMap {
+    fn new(width: u32, height: u32, mut is_ink: impl FnMut(u32, u32) -> bool) -> Self {
+        let ink: Vec<bool> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| is_ink(x, y))
+            .collect();
+        let stride = width as usize + 1;
+        let mut sums = vec![0; stride * (height as usize + 1)];
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                sums[(y + 1) * stride + x + 1] = ink[y * width as usize + x] as u32
+                    + sums[y * stride + x + 1]
+                    + sums[(y + 1) * stride + x]
+                    - sums[y * stride + x];
+            }
+        }
+        Self {
+            width,
+            height,
+            ink,
+            sums,
+        }
+    }
+
+    fn get(&self, x: u32, y: u32) -> bool {
+        self.ink[y as usize * self.width as usize + x as usize]
+    }
+
+    /// `(x, y)` 起 `w × h` 范围内文字的点数
+    fn count(&self, x: u32, y: u32, w: u32, h: u32) -> u32 {
+        let stride = self.width as usize + 1;
+        let at = |x: u32, y: u32| self.sums[y as usize * stride + x as usize];
+        at(x + w, y + h) + at(x, y) - at(x + w, y) - at(x, y + h)
+    }
+}
+
+/// 字库中的一个字及其点
+struct Template<'a> {
+    entry: &'a DictEntry,
+    ink: Vec<(u32, u32)>,
+    blank: Vec<(u32, u32)>,
+    /// 允许不一致的点数
+    allowed: usize,
+}
+
+struct Candidate<'a> {
+    entry: &'a DictEntry,
+    x: u32,
+    y: u32,
+    misses: usize,
+    /// 字的点数减去不一致的点数
+    matched: usize,
+}
+
+impl Candidate<'_> {
+    fn overlaps(&self, other: &Candidate) -> bool {
+        let (a, b) = (self.entry.bitmap(), other.entry.bitmap());
+        self.x < other.x + b.width()
+            && other.x < self.x + a.width()
+            && self.y < other.y + b.height()
+            && other.y < self.y + a.height()
+    }
+}
+
+impl OcrEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 把字库加载到序号 `num`，已有的字库被替换
+    pub fn load(&mut self, num: i32, dict: Dict) {
+        self.dicts.insert(num, dict);
+    }
+
+    /// 选定识字使用的字库，未加载该序号时返回 `false`
+    pub fn set_dict(&mut self, num: i32) -> bool {
+        let loaded = self.dicts.contains_key(&num);
+        if loaded {
+            self.current = num;
+        }
+        loaded
+    }
+
+    /// 当前字库的序号
+    pub fn current(&self) -> i32 {
+        self.current
+    }
+
+    /// 没有加载任何字库
+    pub fn is_empty(&self) -> bool {
+        self.dicts.is_empty()
+    }
+
+    pub fn get(&self, num: i32) -> Option<&Dict> {
+        self.dicts.get(&num)
+    }
+
+    /// 在 `image` 上按 `request` 识字，不使用 `request` 中的 `PicName`
+    pub fn recognize(&self, image: &RgbImage, request: &OcrRequest) -> OcrResult {
+        let dicts: Vec<&Dict> = match request.dict_scope {
+            OcrDictScope::Current => self.dicts.get(&self.current).into_iter().collect(),
+            OcrDictScope::All => self.dicts.values().collect(),
+        };
+        let sim = request.sim.clamp(0.0, 1.0);
+        let templates: Vec<Template> = dicts
+            .iter()
+            .flat_map(|dict| dict.entries())
+            .filter(|entry| request.text.is_empty() || request.text.contains(entry.ch()))
+            .filter_map(|entry| template(entry, sim))
+            .collect();
+
+        let (x1, y1, x2, y2) = image.clip(request.region);
+        let map = InkMap::new(x2 - x1, y2 - y1, |x, y| {
+            let matches = request.color.matches(image.pixel(x1 + x, y1 + y));
+            match request.color_type {
+                OcrColorType::Text => matches,
+                OcrColorType::Background => !matches,
+            }
+        });
+
+        let mut candidates: Vec<Candidate> =
+            templates.iter().flat_map(|t| candidates(&map, t)).collect();
+        candidates.sort_by(|a, b| {
+            b.matched
+                .cmp(&a.matched)
+                .then(a.misses.cmp(&b.misses))
+                .then((a.y, a.x).cmp(&(b.y, b.x)))
+        });
+        let mut accepted: Vec<Candidate> = Vec::new();
+        for candidate in candidates {
+            if !accepted.iter().any(|a| a.overlaps(&candidate)) {
+                accepted.push(candidate);
+            }
+        }
+
+        let glyph = |c: &Candidate| Glyph {
+            ch: c.entry.ch().to_string(),
+            x: (x1 + c.x) as i32,
+            y: (y1 + c.y) as i32,
+        };
+        let lines = match request.lines {
+            OcrLines::Single => {
+                accepted.sort_by_key(|c| (c.x, c.y));
+                if accepted.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![OcrLine::from_glyphs(accepted.iter().map(glyph).collect())]
+                }
+            }
+            OcrLines::Multi => split_lines(accepted)
+                .iter()
+                .map(|line| OcrLine::from_glyphs(line.iter().map(glyph).collect()))
+                .collect(),
+        };
+        OcrResult { lines }
+    }
+}
+
+fn template(entry: &DictEntry, sim: f64) -> Option<Template<'_>> {
+    let bitmap = entry.bitmap();
+    let (ink, blank): (Vec<_>, Vec<_>) = (0..bitmap.width())
+        .flat_map(|x| (0..bitmap.height()).map(move |y| (x, y)))
+        .partition(|&(x, y)| bitmap.get(x, y));
+    if ink.is_empty() {
+        return None;
+    }
+    // 与找图相同，加上微小量以免浮点误差少算一点
+    let allowed = ((1.0 - sim) * ink.len() as f64 + 1e-9).floor() as usize;
+    Some(Template {
+        entry,
+        ink,
+        blank,
+        allowed,
+    })
+}
+
+fn candidates<'a>(map: &InkMap, t: &Template<'a>) -> Vec<Candidate<'a>> {
+    let bitmap = t.entry.bitmap();
+    let (w, h) = (bitmap.width(), bitmap.height());
+    let mut found = Vec::new();
+    if w > map.width || h > map.height {
+        return found;
+    }
+    let points = t.ink.len();
+    for y in 0..=map.height - h {
+        for x in 0..=map.width - w {
+            // 不一致的点数至少为两者点数之差
+            if (map.count(x, y, w, h) as usize).abs_diff(points) > t.allowed {
+                continue;
+            }
+            let mut misses = 0;
+            let fits = t
+                .ink
+                .iter()
+                .map(|&(px, py)| !map.get(x + px, y + py))
+                .chain(t.blank.iter().map(|&(px, py)| map.get(x + px, y + py)))
+                .all(|miss| {
+                    misses += miss as usize;
+                    misses <= t.allowed
+                });
+            if fits {
+                found.push(Candidate {
+                    entry: t.entry,
+                    x,
+                    y,
+                    misses,
+                    matched: points.saturating_sub(misses),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// 纵向重叠的字归为一行，行按从上到下、行内按从左到右排列
+fn split_lines(mut glyphs: Vec<Candidate>) -> Vec<Vec<Candidate>> {
+    glyphs.sort_by_key(|c| (c.y, c.x));
+    let mut lines: Vec<(u32, u32, Vec<Candidate>)> = Vec::new();
+    for glyph in glyphs {
+        let (top, bottom) = (glyph.y, glyph.y + glyph.entry.bitmap().height());
+        match lines.iter_mut().find(|(t, b, _)| top < *b && *t < bottom) {
+            Some((t, b, line)) => {
+                *t = (*t).min(top);
+                *b = (*b).max(bottom);
+                line.push(glyph);
+            }
+            None => lines.push((top, bottom, vec![glyph])),
+        }
+    }
+    lines.sort_by_key(|(top, _, _)| *top);
+    lines
+        .into_iter()
+        .map(|(_, _, mut line)| {
+            line.sort_by_key(|c| c.x);
+            line
+        })
+        .collect()
+}
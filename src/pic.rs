@@ -0,0 +1,353 @@
+//! 找图参数的构造及 `FindPicEx` 结果的解析与生成
+//!
+//! 多个结果之间以 `|` 分隔，每个结果为 `图片,x,y`，
+//! 其中图片按 `TypeT` 为序号或图片名。
+
+use crate::AoJia;
+use crate::bindings::Args;
+use crate::color::Rgb;
+use crate::dispatch::{Call, Dispatch};
+use crate::error::{AoJiaError, ParseError, Result};
+use crate::typed::{PicMatch, Rect};
+
+const WHAT: &str = "FindPicEx 结果";
+
+/// `FindPicEx` 的 `TypeT` 参数，决定结果中图片的表示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PicResultFormat {
+    /// 图片序号，如 `0,100,200|1,30,40`
+    #[default]
+    Index = 0,
+    /// 图片名，如 `a.bmp,100,200|b.bmp,30,40`
+    Name = 1,
+}
+
+impl PicResultFormat {
+    pub fn type_t(self) -> i32 {
+        self as i32
+    }
+}
+
+try_from_i32!(PicResultFormat, "TypeT" { Index, Name });
+
+/// 找图的 `Dir` 参数，即查找顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SearchDir {
+    /// 从左上到右下
+    #[default]
+    LeftTop = 0,
+    /// 从左下到右上
+    LeftBottom = 1,
+    /// 从右上到左下
+    RightTop = 2,
+    /// 从右下到左上
+    RightBottom = 3,
+}
+
+impl SearchDir {
+    pub fn dir(self) -> i32 {
+        self as i32
+    }
+}
+
+try_from_i32!(SearchDir, "Dir" { LeftTop, LeftBottom, RightTop, RightBottom });
+
+/// 找图的 `Type` 参数，决定图片中哪些点不参与比较
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Transparency {
+    /// 图片四角颜色相同时，该颜色视为透明色
+    #[default]
+    Corners = 0,
+    /// 不使用透明色，所有点都参与比较
+    Opaque = 1,
+}
+
+impl Transparency {
+    pub fn ty(self) -> i32 {
+        self as i32
+    }
+}
+
+try_from_i32!(Transparency, "Type" { Corners, Opaque });
+
+/// 拆分 `PicName` 参数中以 `|` 分隔的图片名
+pub fn split_pic_names(pic_name: &str) -> Vec<&str> {
+    if pic_name.is_empty() {
+        Vec::new()
+    } else {
+        pic_name.split('|').collect()
+    }
+}
+
+/// 解析 `FindPicEx` 的返回值
+///
+/// `pic_name` 为调用时传入的 `PicName`，用于在序号与图片名之间互相补全。
+pub fn parse_find_pic_ex(
+    raw: &str,
+    format: PicResultFormat,
+    pic_name: &str,
+) -> std::result::Result<Vec<PicMatch>, ParseError> {
+    let names = split_pic_names(pic_name);
+    let raw_trimmed = raw.trim();
+    if raw_trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    raw_trimmed
+        .split('|')
+        .map(|item| {
+            let err = |reason: String| ParseError::new(WHAT, raw, reason);
+            let fields: Vec<&str> = item.split(',').map(str::trim).collect();
+            let [pic, x, y] = fields[..] else {
+                return Err(err(format!("{item:?} 应为 图片,x,y")));
+            };
+            let coord = |s: &str| {
+                s.parse::<i32>()
+                    .map_err(|_| err(format!("{s:?} 不是有效的坐标")))
+            };
+            let (x, y) = (coord(x)?, coord(y)?);
+
+            let (index, name) = match format {
+                PicResultFormat::Index => {
+                    let index: usize = pic
+                        .parse()
+                        .map_err(|_| err(format!("{pic:?} 不是有效的图片序号")))?;
+                    let name = names
+                        .get(index)
+                        .ok_or_else(|| err(format!("图片序号 {index} 超出 PicName 的范围")))?;
+                    (index, name.to_string())
+                }
+                PicResultFormat::Name => {
+                    let index = names
+                        .iter()
+                        .position(|n| n.eq_ignore_ascii_case(pic))
+                        .ok_or_else(|| err(format!("{pic:?} 不在 PicName 中")))?;
+                    (index, pic.to_string())
+                }
+            };
+            Ok(PicMatch { index, name, x, y })
+        })
+        .collect()
+}
+
+/// 按插件格式生成 `FindPicEx` 的返回值，与 [`parse_find_pic_ex`] 互逆
+pub fn format_find_pic_ex(matches: &[PicMatch], format: PicResultFormat) -> String {
+    matches
+        .iter()
+        .map(|m| match format {
+            PicResultFormat::Index => format!("{},{},{}", m.index, m.x, m.y),
+            PicResultFormat::Name => format!("{},{},{}", m.name, m.x, m.y),
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+impl<B: Dispatch> AoJia<B> {
+    /// `FindPicEx` 的配套方法，返回所有找到的图片
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_pic_ex(
+        &self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        pic_name: &str,
+        color_p: Rgb,
+        sim: f64,
+        dir: i32,
+        ty: i32,
+        format: PicResultFormat,
+    ) -> Result<Vec<PicMatch>> {
+        let raw = self.FindPicEx(
+            x1,
+            y1,
+            x2,
+            y2,
+            pic_name,
+            color_p,
+            sim,
+            dir,
+            ty,
+            format.type_t(),
+        )?;
+        parse_find_pic_ex(&raw, format, pic_name).map_err(|source| AoJiaError::Parse {
+            call: Call::new(
+                "FindPicEx",
+                &Args::FindPicEx(
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    pic_name,
+                    color_p,
+                    sim,
+                    dir,
+                    ty,
+                    format.type_t(),
+                ),
+            ),
+            source,
+        })
+    }
+}
+
+/// `FindPic`/`FindPicEx` 的参数，未设置的参数取插件文档的默认值
+///
+/// ```no_run
+/// # use aojia::*;
+/// # fn demo(aojia: &AoJia<MockAoJia>) -> Result<()> {
+/// let found = PicSearch::new(Rect::new(0, 0, 800, 600), ["ok.bmp", "confirm.bmp"])
+///     .delta(Rgb::new(0x10, 0x10, 0x10))
+///     .dir(SearchDir::RightBottom)
+///     .first(aojia)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PicSearch {
+    pub region: Rect,
+    /// 图片名，调用时以 `|` 相连，因此图片名本身不能含 `|`，见 [`PicSearch::validate`]
+    pub names: Vec<String>,
+    /// `ColorP`，即允许的偏色
+    pub delta: Rgb,
+    pub sim: f64,
+    pub dir: SearchDir,
+    pub transparency: Transparency,
+    /// `FindPicEx` 返回结果的格式，不影响解析后的结果
+    pub format: PicResultFormat,
+}
+
+impl PicSearch {
+    pub fn new<S: Into<String>>(region: Rect, names: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            region,
+            names: names.into_iter().map(Into::into).collect(),
+            delta: Rgb::BLACK,
+            sim: 0.9,
+            dir: SearchDir::default(),
+            transparency: Transparency::default(),
+            format: PicResultFormat::default(),
+        }
+    }
+
+    /// 追加一张图片
+    pub fn pic(mut self, name: impl Into<String>) -> Self {
+        self.names.push(name.into());
+        self
+    }
+
+    pub fn delta(mut self, delta: Rgb) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    pub fn sim(mut self, sim: f64) -> Self {
+        self.sim = sim;
+        self
+    }
+
+    pub fn dir(mut self, dir: SearchDir) -> Self {
+        self.dir = dir;
+        self
+    }
+
+    pub fn transparency(mut self, transparency: Transparency) -> Self {
+        self.transparency = transparency;
+        self
+    }
+
+    pub fn format(mut self, format: PicResultFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// 调用时传入的 `PicName`
+    pub fn pic_name(&self) -> String {
+        self.names.join("|")
+    }
+
+    /// 检查图片名：至少一张，且不能为空或含有 `|`，否则拼接后的 `PicName` 会被拆成别的图片
+    pub fn validate(&self) -> std::result::Result<(), ParseError> {
+        let err = |reason: String| ParseError::new("PicName", &self.pic_name(), reason);
+        if self.names.is_empty() {
+            return Err(err("至少需要一张图片".to_string()));
+        }
+        for (i, name) in self.names.iter().enumerate() {
+            if name.trim().is_empty() {
+                return Err(err(format!("第 {} 个图片名为空", i + 1)));
+            }
+            if name.contains('|') {
+                return Err(err(format!("图片名 {name:?} 含有分隔符 |")));
+            }
+        }
+        Ok(())
+    }
+
+    fn check(&self, name: &str) -> Result<()> {
+        self.validate()
+            .map_err(|source| AoJiaError::InvalidArgument {
+                call: self.call(name),
+                source,
+            })
+    }
+
+    fn call(&self, name: &str) -> Call {
+        let Rect { x1, y1, x2, y2 } = self.region;
+        let (pic_name, dir, ty) = (self.pic_name(), self.dir.dir(), self.transparency.ty());
+        let args = match name {
+            "FindPic" => Args::FindPic(x1, y1, x2, y2, &pic_name, self.delta, self.sim, dir, ty),
+            _ => Args::FindPicEx(
+                x1,
+                y1,
+                x2,
+                y2,
+                &pic_name,
+                self.delta,
+                self.sim,
+                dir,
+                ty,
+                self.format.type_t(),
+            ),
+        };
+        Call::new(name, &args)
+    }
+
+    /// 按 `Dir` 的顺序返回第一个找到的图片，对应 `FindPic`
+    ///
+    /// 图片名无效时不调用插件，返回 `AoJiaError::InvalidArgument`。
+    pub fn first<B: Dispatch>(&self, aojia: &AoJia<B>) -> Result<Option<PicMatch>> {
+        self.check("FindPic")?;
+        let Rect { x1, y1, x2, y2 } = self.region;
+        aojia.find_pic(
+            x1,
+            y1,
+            x2,
+            y2,
+            &self.pic_name(),
+            self.delta,
+            self.sim,
+            self.dir.dir(),
+            self.transparency.ty(),
+        )
+    }
+
+    /// 返回所有找到的图片，对应 `FindPicEx`
+    ///
+    /// 图片名无效时不调用插件，返回 `AoJiaError::InvalidArgument`。
+    pub fn all<B: Dispatch>(&self, aojia: &AoJia<B>) -> Result<Vec<PicMatch>> {
+        self.check("FindPicEx")?;
+        let Rect { x1, y1, x2, y2 } = self.region;
+        aojia.find_pic_ex(
+            x1,
+            y1,
+            x2,
+            y2,
+            &self.pic_name(),
+            self.delta,
+            self.sim,
+            self.dir.dir(),
+            self.transparency.ty(),
+            self.format,
+        )
+    }
+}
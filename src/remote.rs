@@ -0,0 +1,179 @@
+//! 在专用线程上调用插件
+//!
+//! COM 对象属于创建它的单线程套间，`IDispatch` 不能跨线程使用。
+//! [`Remote`] 在专用线程上创建后端，其他线程的调用经由通道转发到该线程执行。
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::AoJia;
+use crate::dispatch::{Arg, Call, Dispatch, Value};
+use crate::error::{AoJiaError, Result};
+
+/// 对象已与调用方断开，对应 RPC_E_DISCONNECTED
+pub(crate) const RPC_E_DISCONNECTED: i32 = 0x8001_0108_u32 as i32;
+/// 未指明的错误，对应 E_FAIL
+const E_FAIL: i32 = 0x8000_4005_u32 as i32;
+
+/// 在插件线程上执行的任务
+pub type Job = Box<dyn FnOnce(&dyn Dispatch) + Send>;
+
+#[derive(Debug)]
+struct Worker {
+    jobs: Option<Sender<Job>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // 关闭通道后线程退出，后端随之在原线程上释放
+        self.jobs.take();
+        // 最后一个句柄可能随任务在插件线程上释放，此时不能等待自身
+        if let Some(thread) = self.thread.take()
+            && thread.thread().id() != thread::current().id()
+        {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 把调用转发到插件线程的后端，可跨线程共享
+///
+/// 所有克隆共用同一个线程，最后一个克隆释放时线程退出。
+#[derive(Debug, Clone)]
+pub struct Remote {
+    worker: Arc<Worker>,
+}
+
+impl Remote {
+    /// 创建插件线程并在其中调用 `factory` 构造后端
+    ///
+    /// 后端无需 `Send`，构造、调用及释放都在该线程上进行。
+    /// 无法创建线程或 `factory` 失败时返回错误。
+    pub fn spawn<D, F>(factory: F) -> Result<Self>
+    where
+        D: Dispatch + 'static,
+        F: FnOnce() -> Result<D> + Send + 'static,
+    {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let (ready, created) = mpsc::sync_channel(1);
+        let thread = thread::Builder::new()
+            .name(String::from("aojia"))
+            .spawn(move || {
+                let backend = match factory() {
+                    Ok(backend) => {
+                        let _ = ready.send(Ok(()));
+                        backend
+                    }
+                    Err(e) => {
+                        let _ = ready.send(Err(e));
+                        return;
+                    }
+                };
+                for job in queue {
+                    // 单个任务崩溃时只丢弃该任务，调用方会收到断开错误
+                    let _ = catch_unwind(AssertUnwindSafe(|| job(&backend)));
+                }
+            })
+            .map_err(|e| {
+                AoJiaError::com(
+                    Call::new("spawn", &[]),
+                    E_FAIL,
+                    format!("无法创建插件线程：{e}"),
+                )
+            })?;
+
+        let worker = Worker {
+            jobs: Some(jobs),
+            thread: Some(thread),
+        };
+        match created.recv() {
+            Ok(Ok(())) => Ok(Self {
+                worker: Arc::new(worker),
+            }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(AoJiaError::com(
+                Call::new("spawn", &[]),
+                RPC_E_DISCONNECTED,
+                "插件线程在构造后端时崩溃",
+            )),
+        }
+    }
+
+    /// 把任务交给插件线程，线程已退出时返回 `false`
+    pub fn submit(&self, job: Job) -> bool {
+        self.worker
+            .jobs
+            .as_ref()
+            .is_some_and(|jobs| jobs.send(job).is_ok())
+    }
+
+    /// 在插件线程上执行 `f` 并等待结果
+    fn run<R: Send + 'static>(
+        &self,
+        call: impl FnOnce() -> Call,
+        f: impl FnOnce(&dyn Dispatch) -> Result<R> + Send + 'static,
+    ) -> Result<R> {
+        let (reply, result) = mpsc::sync_channel(1);
+        let submitted = self.submit(Box::new(move |backend| {
+            let _ = reply.send(f(backend));
+        }));
+        let disconnected = |message| AoJiaError::com(call(), RPC_E_DISCONNECTED, message);
+        if !submitted {
+            return Err(disconnected("插件线程已退出"));
+        }
+        result
+            .recv()
+            .unwrap_or_else(|_| Err(disconnected("插件线程处理调用时崩溃")))
+    }
+}
+
+impl Dispatch for Remote {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        let owned_name = name.to_string();
+        let mut owned_args = args.to_vec();
+        let (ret, outs) = self.run(
+            || Call::new(name, args),
+            move |backend| {
+                let ret = backend.invoke(&owned_name, &mut owned_args)?;
+                Ok((ret, owned_args))
+            },
+        )?;
+        for (arg, out) in args.iter_mut().zip(outs) {
+            if arg.is_out() {
+                *arg = out;
+            }
+        }
+        Ok(ret)
+    }
+
+    fn resolve(&self, name: &str) -> Result<()> {
+        let owned_name = name.to_string();
+        self.run(
+            || Call::new(name, &[]),
+            move |backend| backend.resolve(&owned_name),
+        )
+    }
+}
+
+/// 可在多个线程间共享的插件对象，调用在专用线程上执行
+pub type AoJiaHandle = AoJia<Remote>;
+
+impl AoJia<Remote> {
+    /// 创建插件线程，见 [`Remote::spawn`]
+    pub fn spawn<D, F>(factory: F) -> Result<Self>
+    where
+        D: Dispatch + 'static,
+        F: FnOnce() -> Result<D> + Send + 'static,
+    {
+        Remote::spawn(factory).map(Self::with_backend)
+    }
+
+    /// 在插件线程上加载插件
+    #[cfg(windows)]
+    pub fn spawn_with_path(a_regj_path: String, ao_jia_path: String) -> Result<Self> {
+        Self::spawn(move || crate::ComBackend::new_with_path(a_regj_path, ao_jia_path))
+    }
+}
@@ -0,0 +1,186 @@
+//! 依据 [`Journal`](crate::Journal) 记录的日志重放调用结果
+//!
+//! 调用必须与日志逐条一致，第一次不一致时返回 `AoJiaError::Replay`，
+//! 此后的调用都以同样的错误失败，避免在错误的状态上继续运行。
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::dispatch::{Arg, Call, Dispatch, Value};
+use crate::error::{AoJiaError, Result};
+use crate::journal::{JournalEntry, JournalError};
+
+#[derive(Debug)]
+struct State {
+    entries: VecDeque<(usize, JournalEntry)>,
+    /// 第一次不一致时的错误信息
+    diverged: Option<String>,
+}
+
+/// 按日志应答调用的后端
+#[derive(Debug)]
+pub struct Replay {
+    state: Mutex<State>,
+    /// 日志中与此值相等的参数匹配任意值，用于遮盖过的参数
+    placeholder: Option<Value>,
+}
+
+impl Replay {
+    /// 依次重放 `entries`，错误信息中的行号为条目的序号
+    pub fn new(entries: impl IntoIterator<Item = JournalEntry>) -> Self {
+        Self::numbered(entries.into_iter().enumerate().map(|(i, e)| (i + 1, e)))
+    }
+
+    fn numbered(entries: impl IntoIterator<Item = (usize, JournalEntry)>) -> Self {
+        Self {
+            state: Mutex::new(State {
+                entries: entries.into_iter().collect(),
+                diverged: None,
+            }),
+            placeholder: None,
+        }
+    }
+
+    /// 解析 JSONL 格式的日志，忽略空行
+    pub fn parse(journal: &str) -> io::Result<Self> {
+        let entries = journal
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str::<JournalEntry>(line)
+                    .map(|entry| (i + 1, entry))
+                    .map_err(|e| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("第 {} 行：{e}", i + 1))
+                    })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::numbered(entries))
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// 日志中等于 `placeholder` 的参数匹配任意值，通常为遮盖时使用的值
+    pub fn placeholder(mut self, placeholder: impl Into<Value>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// 尚未重放的条目数
+    pub fn remaining(&self) -> usize {
+        self.state().entries.len()
+    }
+
+    /// 确认日志已全部重放且没有不一致
+    pub fn finish(&self) -> Result<()> {
+        let state = self.state();
+        if let Some(reason) = &state.diverged {
+            return Err(replay_error(Call::new("finish", &[]), reason.clone()));
+        }
+        match state.entries.front() {
+            Some((line, entry)) => Err(replay_error(
+                Call::new("finish", &[]),
+                format!(
+                    "日志还有 {} 条未重放，下一条为第 {line} 行的 {}",
+                    state.entries.len(),
+                    entry.method
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn matches(&self, recorded: &Value, actual: &Value) -> bool {
+        if self.placeholder.as_ref() == Some(recorded) {
+            return true;
+        }
+        match (recorded, actual) {
+            // 日志中的整数一律还原为 I32，超出范围时为 I64
+            (Value::I32(_) | Value::I64(_), Value::I32(_) | Value::I64(_)) => {
+                recorded.to_i64() == actual.to_i64()
+            }
+            _ => recorded == actual,
+        }
+    }
+
+    /// 与日志比对，不一致时返回原因
+    fn check(&self, line: usize, entry: &JournalEntry, name: &str, args: &[Arg]) -> Option<String> {
+        if entry.method != name {
+            return Some(format!(
+                "第 {line} 行应调用 {}，实际调用 {name}",
+                entry.method
+            ));
+        }
+        let inputs: Vec<&Value> = args
+            .iter()
+            .filter(|a| !a.is_out())
+            .map(Arg::value)
+            .collect();
+        let outs = args.iter().filter(|a| a.is_out()).count();
+        if inputs.len() != entry.args.len() || outs != entry.outs.len() {
+            return Some(format!(
+                "第 {line} 行的 {name} 记录了 {} 个传值参数、{} 个传址参数，实际为 {}、{outs}",
+                entry.args.len(),
+                entry.outs.len(),
+                inputs.len()
+            ));
+        }
+        entry
+            .args
+            .iter()
+            .zip(&inputs)
+            .position(|(recorded, actual)| !self.matches(recorded, actual))
+            .map(|i| {
+                format!(
+                    "第 {line} 行的 {name} 第 {} 个传值参数应为 {}，实际为 {}",
+                    i + 1,
+                    entry.args[i],
+                    inputs[i]
+                )
+            })
+    }
+}
+
+fn replay_error(call: Call, reason: String) -> AoJiaError {
+    AoJiaError::Replay { call, reason }
+}
+
+impl Dispatch for Replay {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        let call = Call::new(name, args);
+        let mut state = self.state();
+        if let Some(reason) = &state.diverged {
+            return Err(replay_error(call, format!("此前已与日志不一致：{reason}")));
+        }
+
+        let Some((line, entry)) = state.entries.pop_front() else {
+            let reason = format!("日志已全部重放，多出调用 {name}");
+            state.diverged = Some(reason.clone());
+            return Err(replay_error(call, reason));
+        };
+        if let Some(reason) = self.check(line, &entry, name, args) {
+            state.diverged = Some(reason.clone());
+            return Err(replay_error(call, reason));
+        }
+
+        if let Some(message) = entry.error {
+            // 旧日志没有错误种类，按 COM 错误重放
+            let kind = entry.kind.unwrap_or(JournalError::Com);
+            return Err(kind.into_error(call, entry.hresult, message));
+        }
+        let mut outs = entry.outs.into_iter();
+        for arg in args.iter_mut().filter(|a| a.is_out()) {
+            *arg = Arg::Out(outs.next().unwrap_or_default());
+        }
+        Ok(entry.ret)
+    }
+}
@@ -0,0 +1,133 @@
+//! 插件接口描述，数据见 `spec/aojia.json`
+//!
+//! 包装方法及其文档由 build.rs 依据此文件生成，本模块同时被 build.rs 引用，
+//! 因此只依赖 serde。
+
+use serde::Deserialize;
+
+/// 仓库中的接口描述文件
+pub const SOURCE: &str = include_str!("../spec/aojia.json");
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Spec {
+    pub methods: Vec<MethodSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MethodSpec {
+    pub name: String,
+    #[serde(default)]
+    pub doc: String,
+    pub edition: Edition,
+    pub returns: Type,
+    /// 表示调用失败的返回值
+    #[serde(default)]
+    pub failure: Vec<i64>,
+    #[serde(default)]
+    pub params: Vec<ParamSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ParamSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: Type,
+    #[serde(default)]
+    pub dir: Direction,
+    /// 插件文档给出的默认值
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Type {
+    I32,
+    I64,
+    F64,
+    Bool,
+    Str,
+    /// 窗口句柄，对应 `Hwnd`
+    Hwnd,
+    /// `RRGGBB` 颜色，对应 `Rgb`
+    Rgb,
+    /// `RRGGBB-DRDGDB|...` 颜色描述，对应 `ColorSpec`
+    #[serde(rename = "color_spec")]
+    ColorSpec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    In,
+    Out,
+}
+
+/// 免费版或收费版插件提供
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Edition {
+    Free,
+    Paid,
+}
+
+impl Spec {
+    pub fn parse(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+
+    /// 解析仓库自带的接口描述
+    pub fn builtin() -> Self {
+        Self::parse(SOURCE).expect("spec/aojia.json 格式错误")
+    }
+
+    pub fn method(&self, name: &str) -> Option<&MethodSpec> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+}
+
+impl MethodSpec {
+    /// 对应的 Rust 参数列表，如 `Hwnd: i32, Width: &mut i32`
+    pub fn rust_params(&self) -> String {
+        self.params
+            .iter()
+            .map(|p| format!("{}: {}", p.name, p.rust_type()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl ParamSpec {
+    pub fn rust_type(&self) -> String {
+        match self.dir {
+            Direction::In => self.ty.rust_in().to_string(),
+            Direction::Out => format!("&mut {}", self.ty.rust_owned()),
+        }
+    }
+}
+
+impl Type {
+    /// 作为传值参数时的类型
+    pub fn rust_in(self) -> &'static str {
+        match self {
+            Type::Str => "&str",
+            Type::ColorSpec => "&ColorSpec",
+            _ => self.rust_owned(),
+        }
+    }
+
+    /// 作为返回值或传址参数时的类型
+    pub fn rust_owned(self) -> &'static str {
+        match self {
+            Type::I32 => "i32",
+            Type::I64 => "i64",
+            Type::F64 => "f64",
+            Type::Bool => "bool",
+            Type::Str => "String",
+            Type::Hwnd => "Hwnd",
+            Type::Rgb => "Rgb",
+            Type::ColorSpec => "ColorSpec",
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! 以结构化返回值代替传址参数的配套方法
+
+use crate::AoJia;
+use crate::color::Rgb;
+use crate::dispatch::Dispatch;
+use crate::error::Result;
+use crate::window::Hwnd;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Size {
+    pub width: i32,
+    pub height: i32,
+}
+
+/// 以左上角 `(x1, y1)` 与右下角 `(x2, y2)` 表示的区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Rect {
+    pub const fn new(x1: i32, y1: i32, x2: i32, y2: i32) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    /// 由左上角及大小构造
+    pub const fn from_origin(origin: Point, size: Size) -> Self {
+        Self::new(
+            origin.x,
+            origin.y,
+            origin.x + size.width,
+            origin.y + size.height,
+        )
+    }
+}
+
+/// 找图结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PicMatch {
+    /// 图片在 `PicName` 列表中的序号，从 0 开始
+    pub index: usize,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl PicMatch {
+    pub fn point(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+}
+
+/// `GetOs` 的结果
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OsInfo {
+    /// 版本号
+    pub version: String,
+    /// 版本名
+    pub version_name: String,
+    /// 内部版本号
+    pub build: i32,
+    /// 系统目录
+    pub system_dir: String,
+}
+
+/// `GetCPU` 的结果
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CpuInfo {
+    /// 型号
+    pub model: String,
+    /// 序列号
+    pub id: String,
+}
+
+impl<B: Dispatch> AoJia<B> {
+    /// `FindPic` 的配套方法，未找到（返回 -1）时为 `None`
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_pic(
+        &self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        pic_name: &str,
+        color_p: Rgb,
+        sim: f64,
+        dir: i32,
+        ty: i32,
+    ) -> Result<Option<PicMatch>> {
+        let mut name = String::new();
+        let (mut x, mut y) = (-1, -1);
+        let ret = self.FindPic(
+            x1, y1, x2, y2, pic_name, color_p, sim, dir, ty, &mut name, &mut x, &mut y,
+        )?;
+        Ok(usize::try_from(ret)
+            .ok()
+            .map(|index| PicMatch { index, name, x, y }))
+    }
+
+    /// `GetOs` 的配套方法
+    pub fn get_os(&self, ty: i32) -> Result<OsInfo> {
+        let mut info = OsInfo::default();
+        self.GetOs(
+            &mut info.version,
+            &mut info.version_name,
+            &mut info.build,
+            &mut info.system_dir,
+            ty,
+        )?;
+        Ok(info)
+    }
+
+    /// `GetCPU` 的配套方法
+    pub fn get_cpu(&self) -> Result<CpuInfo> {
+        let mut info = CpuInfo::default();
+        self.GetCPU(&mut info.model, &mut info.id)?;
+        Ok(info)
+    }
+
+    /// `GetClientSize` 的配套方法
+    pub fn get_client_size(&self, hwnd: Hwnd) -> Result<Size> {
+        let mut size = Size::default();
+        self.GetClientSize(hwnd, &mut size.width, &mut size.height)?;
+        Ok(size)
+    }
+
+    /// `GetWindowSize` 的配套方法
+    pub fn get_window_size(&self, hwnd: Hwnd) -> Result<Size> {
+        let mut size = Size::default();
+        self.GetWindowSize(hwnd, &mut size.width, &mut size.height)?;
+        Ok(size)
+    }
+
+    /// `ClientToScreen` 的配套方法
+    pub fn client_to_screen(&self, hwnd: Hwnd) -> Result<Point> {
+        let mut point = Point::default();
+        self.ClientToScreen(hwnd, &mut point.x, &mut point.y)?;
+        Ok(point)
+    }
+
+    /// `ClientOrScreen` 的配套方法
+    pub fn client_or_screen(&self, hwnd: Hwnd, point: Point, ty: i32) -> Result<Point> {
+        let mut out = Point::default();
+        self.ClientOrScreen(hwnd, point.x, point.y, &mut out.x, &mut out.y, ty)?;
+        Ok(out)
+    }
+
+    /// `GetMousePos` 的配套方法
+    pub fn get_mouse_pos(&self, ty: i32) -> Result<Point> {
+        let mut point = Point::default();
+        self.GetMousePos(&mut point.x, &mut point.y, ty)?;
+        Ok(point)
+    }
+}
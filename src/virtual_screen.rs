@@ -0,0 +1,541 @@
+//! 以截图代替屏幕的后端
+//!
+//! 找图、识字等依赖屏幕内容的调用在当前帧上执行，鼠标键盘调用只记录下来，
+//! 帧按设定的条件依次切换，停在最后一帧。可在没有桌面的 Linux 上运行完整的脚本流程。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::color::{ColorSpec, Rgb};
+use crate::dict::Dict;
+use crate::dispatch::{Arg, Call, Dispatch, Value};
+use crate::error::{AoJiaError, ParseError, Result};
+use crate::imgsearch::{self, ImageError, RgbImage};
+use crate::ocr::{OcrRequest, OcrResult, format_ocr};
+use crate::ocr_engine::OcrEngine;
+use crate::pic::{PicResultFormat, PicSearch, format_find_pic_ex};
+use crate::typed::{Point, Rect};
+
+/// 参数错误，对应 E_INVALIDARG
+const E_INVALIDARG: i32 = 0x8007_0057_u32 as i32;
+/// 未实现，对应 E_NOTIMPL
+const E_NOTIMPL: i32 = 0x8000_4001_u32 as i32;
+
+/// 默认视为键鼠操作的方法
+const INPUT_METHODS: [&str; 5] = ["LeftClick", "LeftDown", "LeftUp", "MoveTo", "WheelDown"];
+
+/// 在当前帧上处理某个方法的函数，须自行写回传址参数
+///
+/// 调用时不持有内部的锁，函数中可以再调用同一个 `VirtualScreen`。
+pub type ScreenHandler = Box<dyn Fn(&RgbImage, &mut [Arg]) -> Result<Value> + Send + Sync>;
+
+/// 在当前帧上识字的函数，与 [`ScreenHandler`] 相同，调用时不持有内部的锁
+pub type OcrHook = Box<dyn Fn(&RgbImage, &OcrRequest) -> OcrResult + Send + Sync>;
+
+/// 一帧截图
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// 帧的名称，从文件读取时为文件名
+    pub name: String,
+    pub image: RgbImage,
+}
+
+impl Frame {
+    pub fn new(name: impl Into<String>, image: RgbImage) -> Self {
+        Self {
+            name: name.into(),
+            image,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> std::result::Result<Self, ImageError> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+        Ok(Self::new(name, RgbImage::open(path)?))
+    }
+}
+
+/// 切换到下一帧的条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Advance {
+    /// 每次键鼠操作之后
+    EveryInput,
+    /// 每次调用该方法之后
+    After(String),
+    /// 在同一帧上找图、识字等若干次之后，模拟轮询期间画面的变化
+    ScreenReads(u32),
+}
+
+/// 一次键鼠操作及执行时所在的帧
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputEvent {
+    pub frame: usize,
+    pub call: Call,
+}
+
+#[derive(Debug)]
+struct State {
+    frame: usize,
+    /// 当前帧上的屏幕调用次数
+    reads: u32,
+    mouse: Point,
+    /// `SetPath` 设置的路径，图片相对此路径读取
+    path: PathBuf,
+    pics: HashMap<String, RgbImage>,
+    engine: OcrEngine,
+    inputs: Vec<InputEvent>,
+}
+
+/// 以一组截图作为屏幕的后端
+///
+/// - `FindPic`/`FindPicEx` 由 [`imgsearch`] 在当前帧上查找，图片先从
+///   [`VirtualScreen::pic`] 登记的图片中找，再相对 `SetPath` 的路径读取；
+/// - `LoadDict`/`SetDict` 相对 `SetPath` 的路径读取并选定字库，`Ocr` 由 [`OcrEngine`]
+///   在当前帧上识字，设置了 [`VirtualScreen::ocr_with`] 时改由该函数识字；
+/// - 键鼠操作记入 [`VirtualScreen::inputs`]，`MoveTo` 同时更新 `GetMousePos` 的结果；
+/// - `GetClientSize`/`GetWindowSize` 返回当前帧的尺寸；
+/// - 其余调用不做任何事，返回 1。
+///
+/// ```no_run
+/// # use aojia::*;
+/// # fn demo() -> std::result::Result<(), ImageError> {
+/// let screen = VirtualScreen::open_dir("recordings/login")?
+///     .pic_dir("assets")
+///     .advance_on(Advance::EveryInput);
+/// let aojia = AoJia::with_backend(&screen);
+/// # Ok(())
+/// # }
+/// ```
+pub struct VirtualScreen {
+    frames: Vec<Frame>,
+    state: Mutex<State>,
+    advance: Vec<Advance>,
+    input_methods: HashSet<String>,
+    handlers: HashMap<String, ScreenHandler>,
+    ocr: Option<OcrHook>,
+}
+
+impl VirtualScreen {
+    /// 至少需要一帧，没有帧时返回 `ImageError::Format`
+    pub fn new(frames: impl IntoIterator<Item = Frame>) -> std::result::Result<Self, ImageError> {
+        let frames: Vec<Frame> = frames.into_iter().collect();
+        if frames.is_empty() {
+            return Err(ImageError::Format("没有截图".to_string()));
+        }
+        Ok(Self {
+            frames,
+            state: Mutex::new(State {
+                frame: 0,
+                reads: 0,
+                mouse: Point::default(),
+                path: PathBuf::new(),
+                pics: HashMap::new(),
+                engine: OcrEngine::new(),
+                inputs: Vec::new(),
+            }),
+            advance: Vec::new(),
+            input_methods: INPUT_METHODS.iter().map(|m| m.to_string()).collect(),
+            handlers: HashMap::new(),
+            ocr: None,
+        })
+    }
+
+    /// 按给出的顺序读取截图文件
+    pub fn open<P: AsRef<Path>>(
+        paths: impl IntoIterator<Item = P>,
+    ) -> std::result::Result<Self, ImageError> {
+        let frames = paths
+            .into_iter()
+            .map(Frame::open)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Self::new(frames)
+    }
+
+    /// 读取目录中的 BMP 及 PNG 文件，按文件名排序
+    pub fn open_dir(dir: impl AsRef<Path>) -> std::result::Result<Self, ImageError> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_image = path.extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("bmp") || ext.eq_ignore_ascii_case("png")
+            });
+            if is_image && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Self::open(paths)
+    }
+
+    /// 登记图片，`FindPic` 中的图片名优先在登记的图片中查找，不区分大小写
+    pub fn pic(self, name: &str, image: RgbImage) -> Self {
+        self.state().pics.insert(pic_key(name), image);
+        self
+    }
+
+    /// 读取图片的初始路径，脚本调用 `SetPath` 后改为其参数
+    pub fn pic_dir(self, dir: impl Into<PathBuf>) -> Self {
+        self.state().path = dir.into();
+        self
+    }
+
+    /// 把字库加载到序号 `num`，与脚本调用 `LoadDict` 相同
+    pub fn dict(self, num: i32, dict: Dict) -> Self {
+        self.state().engine.load(num, dict);
+        self
+    }
+
+    /// 增加一个切换帧的条件，多个条件之间为“或”
+    pub fn advance_on(mut self, advance: Advance) -> Self {
+        self.advance.push(advance);
+        self
+    }
+
+    /// 把 `name` 也视为键鼠操作
+    pub fn input_method(mut self, name: &str) -> Self {
+        self.input_methods.insert(name.to_string());
+        self
+    }
+
+    /// 由 `handler` 在当前帧上处理 `name`，用于找色等尚未内置的屏幕调用
+    pub fn handle(
+        mut self,
+        name: &str,
+        handler: impl Fn(&RgbImage, &mut [Arg]) -> Result<Value> + Send + Sync + 'static,
+    ) -> Self {
+        self.handlers.insert(name.to_string(), Box::new(handler));
+        self
+    }
+
+    /// 以 `ocr` 代替字库识字，返回值按调用的 `TypeR`、`TypeT` 及 `HLine` 转为插件格式
+    pub fn ocr_with(
+        mut self,
+        ocr: impl Fn(&RgbImage, &OcrRequest) -> OcrResult + Send + Sync + 'static,
+    ) -> Self {
+        self.ocr = Some(Box::new(ocr));
+        self
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// 当前帧的序号
+    pub fn frame_index(&self) -> usize {
+        self.state().frame
+    }
+
+    pub fn frame(&self) -> &Frame {
+        &self.frames[self.frame_index()]
+    }
+
+    /// 切换到第 `index` 帧，超出范围时停在最后一帧
+    pub fn set_frame(&self, index: usize) {
+        let mut state = self.state();
+        state.frame = index.min(self.frames.len() - 1);
+        state.reads = 0;
+    }
+
+    /// 切换到下一帧，已是最后一帧时返回 `false`
+    pub fn next_frame(&self) -> bool {
+        Self::advance(&mut self.state(), self.frames.len())
+    }
+
+    /// 按顺序列出键鼠操作
+    pub fn inputs(&self) -> Vec<InputEvent> {
+        self.state().inputs.clone()
+    }
+
+    pub fn take_inputs(&self) -> Vec<InputEvent> {
+        std::mem::take(&mut self.state().inputs)
+    }
+
+    /// `MoveTo` 移动到的位置
+    pub fn mouse(&self) -> Point {
+        self.state().mouse
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn advance(state: &mut State, frames: usize) -> bool {
+        state.reads = 0;
+        if state.frame + 1 < frames {
+            state.frame += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 取出图片，未登记时相对当前路径读取并缓存
+    fn load_pic(&self, name: &str, call: &Call) -> Result<RgbImage> {
+        let key = pic_key(name);
+        let path = {
+            let state = self.state();
+            if let Some(image) = state.pics.get(&key) {
+                return Ok(image.clone());
+            }
+            state.path.join(name.trim())
+        };
+        let image = RgbImage::open(&path).map_err(|e| {
+            AoJiaError::com(
+                call.clone(),
+                E_INVALIDARG,
+                format!("无法读取图片 {}：{e}", path.display()),
+            )
+        })?;
+        self.state().pics.insert(key, image.clone());
+        Ok(image)
+    }
+
+    fn pic_search(&self, params: &Params) -> Result<(PicSearch, Vec<RgbImage>)> {
+        let pic_name = params.string(4)?;
+        let search = PicSearch::new(params.rect()?, crate::pic::split_pic_names(&pic_name))
+            .delta(params.parse::<Rgb>(5)?)
+            .sim(params.f64(6)?)
+            .dir(params.mode(7)?)
+            .transparency(params.mode(8)?);
+        let pics = search
+            .names
+            .iter()
+            .map(|name| self.load_pic(name, params.call))
+            .collect::<Result<_>>()?;
+        Ok((search, pics))
+    }
+
+    fn ocr_request(params: &Params) -> Result<OcrRequest> {
+        Ok(
+            OcrRequest::new(params.rect()?, params.parse::<ColorSpec>(5)?)
+                .text(params.string(4)?)
+                .sim(params.f64(6)?)
+                .color_type(params.mode(7)?)
+                .dict_scope(params.mode(8)?)
+                .output(params.mode(9)?)
+                .lines(params.mode(10)?)
+                .line_separator(params.string(11)?)
+                .pic_name(params.string(12)?),
+        )
+    }
+
+    /// 在第 `frame` 帧上执行调用，返回值及是否为屏幕调用
+    ///
+    /// 只在读写状态时短暂加锁，调用用户的函数时不持有锁。
+    fn dispatch(&self, frame: usize, name: &str, args: &mut [Arg]) -> Result<(Value, bool)> {
+        let call = Call::new(name, args);
+        let params = Params::new(&call);
+        let image = &self.frames[frame].image;
+        match name {
+            "SetPath" => {
+                self.state().path = PathBuf::from(params.string(0)?);
+                Ok((Value::I32(1), false))
+            }
+            "FindPic" => {
+                let (search, pics) = self.pic_search(&params)?;
+                let (ret, outs) = match imgsearch::find_pic(image, &pics, &search) {
+                    Some(m) => (m.index as i32, [m.name.into(), m.x.into(), m.y.into()]),
+                    None => (-1, [Value::from(""), Value::I32(-1), Value::I32(-1)]),
+                };
+                write_outs(args, outs);
+                Ok((Value::I32(ret), true))
+            }
+            "FindPicEx" => {
+                let (search, pics) = self.pic_search(&params)?;
+                let format: PicResultFormat = params.mode(9)?;
+                let matches = imgsearch::find_pic_ex(image, &pics, &search);
+                Ok((format_find_pic_ex(&matches, format).into(), true))
+            }
+            "LoadDict" => {
+                let path = self.state().path.join(params.string(1)?.trim());
+                let dict = Dict::open(&path).map_err(|e| {
+                    AoJiaError::com(
+                        call.clone(),
+                        E_INVALIDARG,
+                        format!("无法读取字库 {}：{e}", path.display()),
+                    )
+                })?;
+                self.state().engine.load(params.i32(0)?, dict);
+                Ok((Value::I32(1), false))
+            }
+            "SetDict" => {
+                let loaded = self.state().engine.set_dict(params.i32(0)?);
+                Ok((Value::I32(loaded as i32), false))
+            }
+            "Ocr" => {
+                let request = Self::ocr_request(&params)?;
+                let pic;
+                let image = if request.pic_name.is_empty() {
+                    image
+                } else {
+                    pic = self.load_pic(&request.pic_name, &call)?;
+                    &pic
+                };
+                let result = match &self.ocr {
+                    Some(ocr) => ocr(image, &request),
+                    None => {
+                        let state = self.state();
+                        if state.engine.is_empty() {
+                            return Err(AoJiaError::com(
+                                call.clone(),
+                                E_NOTIMPL,
+                                "没有加载字库，也没有设置识字函数",
+                            ));
+                        }
+                        state.engine.recognize(image, &request)
+                    }
+                };
+                let raw = format_ocr(
+                    &result,
+                    request.output,
+                    request.lines,
+                    &request.line_separator,
+                );
+                Ok((raw.into(), true))
+            }
+            "MoveTo" => {
+                self.state().mouse = Point::new(params.i32(0)?, params.i32(1)?);
+                Ok((Value::I32(1), false))
+            }
+            "GetMousePos" => {
+                let mouse = self.mouse();
+                write_outs(args, [mouse.x.into(), mouse.y.into()]);
+                Ok((Value::I32(1), false))
+            }
+            "GetClientSize" | "GetWindowSize" => {
+                let (width, height) = (image.width() as i32, image.height() as i32);
+                write_outs(args, [width.into(), height.into()]);
+                Ok((Value::I32(1), false))
+            }
+            _ => match self.handlers.get(name) {
+                Some(handler) => Ok((handler(image, args)?, true)),
+                None => Ok((Value::I32(1), false)),
+            },
+        }
+    }
+}
+
+impl Dispatch for VirtualScreen {
+    fn invoke(&self, name: &str, args: &mut [Arg]) -> Result<Value> {
+        let is_input = self.input_methods.contains(name);
+        let frame = {
+            let mut state = self.state();
+            let frame = state.frame;
+            if is_input {
+                state.inputs.push(InputEvent {
+                    frame,
+                    call: Call::new(name, args),
+                });
+            }
+            frame
+        };
+
+        let (ret, screen_read) = self.dispatch(frame, name, args)?;
+        let mut state = self.state();
+        if screen_read {
+            state.reads += 1;
+        }
+        let advance = self.advance.iter().any(|advance| match advance {
+            Advance::EveryInput => is_input,
+            Advance::After(method) => method == name,
+            Advance::ScreenReads(n) => screen_read && state.reads >= *n,
+        });
+        if advance {
+            Self::advance(&mut state, self.frames.len());
+        }
+        Ok(ret)
+    }
+}
+
+impl std::fmt::Debug for VirtualScreen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VirtualScreen")
+            .field("frames", &self.frames.len())
+            .field("state", &self.state())
+            .field("advance", &self.advance)
+            .field("input_methods", &self.input_methods)
+            .finish_non_exhaustive()
+    }
+}
+
+fn pic_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// 依次写回传址参数
+fn write_outs(args: &mut [Arg], outs: impl IntoIterator<Item = Value>) {
+    let mut outs = outs.into_iter();
+    for arg in args.iter_mut().filter(|a| a.is_out()) {
+        *arg = Arg::Out(outs.next().unwrap_or_default());
+    }
+}
+
+/// 按位置读取传值参数，缺少的参数视为空
+struct Params<'a> {
+    call: &'a Call,
+    values: Vec<&'a Value>,
+}
+
+impl<'a> Params<'a> {
+    fn new(call: &'a Call) -> Self {
+        Self {
+            call,
+            values: call.inputs(),
+        }
+    }
+
+    fn value(&self, i: usize) -> &Value {
+        self.values.get(i).copied().unwrap_or(&Value::Empty)
+    }
+
+    fn conversion(&self, source: crate::error::ConversionError) -> AoJiaError {
+        AoJiaError::Conversion {
+            call: self.call.clone(),
+            source,
+        }
+    }
+
+    fn i32(&self, i: usize) -> Result<i32> {
+        self.value(i).to_i32().map_err(|e| self.conversion(e))
+    }
+
+    fn f64(&self, i: usize) -> Result<f64> {
+        self.value(i).to_f64().map_err(|e| self.conversion(e))
+    }
+
+    fn string(&self, i: usize) -> Result<String> {
+        self.value(i).to_string().map_err(|e| self.conversion(e))
+    }
+
+    fn parse<T: FromStr<Err = ParseError>>(&self, i: usize) -> Result<T> {
+        self.string(i)?
+            .parse()
+            .map_err(|source| AoJiaError::InvalidArgument {
+                call: self.call.clone(),
+                source,
+            })
+    }
+
+    /// 以整数表示的模式，未知的值为 `InvalidArgument`
+    fn mode<T: TryFrom<i32, Error = ParseError>>(&self, i: usize) -> Result<T> {
+        T::try_from(self.i32(i)?).map_err(|source| AoJiaError::InvalidArgument {
+            call: self.call.clone(),
+            source,
+        })
+    }
+
+    /// 前四个参数组成的区域
+    fn rect(&self) -> Result<Rect> {
+        Ok(Rect::new(
+            self.i32(0)?,
+            self.i32(1)?,
+            self.i32(2)?,
+            self.i32(3)?,
+        ))
+    }
+}
@@ -0,0 +1,144 @@
+//! 轮询等待图片、文字或窗口出现
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::AoJia;
+use crate::dispatch::Dispatch;
+use crate::error::{AoJiaError, Result};
+use crate::ocr::{OcrOutput, OcrRequest};
+use crate::pic::PicSearch;
+use crate::typed::{PicMatch, Point};
+use crate::window::Hwnd;
+
+// 等待期间检查取消的最长间隔
+const CANCEL_CHECK: Duration = Duration::from_millis(10);
+
+/// 取消等待的标记，克隆后在其他线程调用 [`CancelToken::cancel`]
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 等待的超时、轮询间隔及取消标记
+#[derive(Debug, Clone)]
+pub struct Wait {
+    pub timeout: Duration,
+    pub interval: Duration,
+    pub cancel: Option<CancelToken>,
+}
+
+impl Wait {
+    /// 默认每 100 毫秒检查一次
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            interval: Duration::from_millis(100),
+            cancel: None,
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn cancel(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// 反复调用 `poll` 直到返回 `Some`，`poll` 出错时立即返回
+    ///
+    /// 至少检查一次；超时返回 `AoJiaError::WaitTimeout`，取消返回 `AoJiaError::Cancelled`，
+    /// `what` 用于错误信息。
+    pub fn until<T>(&self, what: &str, mut poll: impl FnMut() -> Result<Option<T>>) -> Result<T> {
+        let start = Instant::now();
+        let mut polls = 0;
+        loop {
+            if self.cancelled() {
+                return Err(AoJiaError::Cancelled {
+                    what: what.to_string(),
+                    polls,
+                });
+            }
+            polls += 1;
+            if let Some(found) = poll()? {
+                return Ok(found);
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= self.timeout {
+                return Err(AoJiaError::WaitTimeout {
+                    what: what.to_string(),
+                    polls,
+                    elapsed,
+                });
+            }
+            self.sleep(self.interval.min(self.timeout - elapsed));
+        }
+    }
+
+    // 分段休眠，以便及时响应取消
+    fn sleep(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        while !self.cancelled() {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            thread::sleep((deadline - now).min(CANCEL_CHECK));
+        }
+    }
+}
+
+impl<B: Dispatch> AoJia<B> {
+    /// 等待任一图片出现，返回第一个找到的图片
+    pub fn wait_for_pic(&self, search: &PicSearch, wait: &Wait) -> Result<PicMatch> {
+        let what = format!("图片 {}", search.pic_name());
+        wait.until(&what, || search.first(self))
+    }
+
+    /// 等待所有图片消失
+    pub fn wait_for_pic_gone(&self, search: &PicSearch, wait: &Wait) -> Result<()> {
+        let what = format!("图片 {} 消失", search.pic_name());
+        wait.until(&what, || Ok(search.first(self)?.is_none().then_some(())))
+    }
+
+    /// 等待识别出 `text`，返回其首个字的坐标
+    ///
+    /// 为取得坐标，识字时总是使用 `OcrOutput::Positions`。
+    pub fn wait_for_text(&self, request: &OcrRequest, text: &str, wait: &Wait) -> Result<Point> {
+        let request = request.clone().output(OcrOutput::Positions);
+        let what = format!("文字 {text:?}");
+        wait.until(&what, || Ok(request.run(self)?.find_text(text)))
+    }
+
+    /// 等待类名及标题符合条件的窗口出现，参数含义同 `FindWindow`
+    pub fn wait_for_window(&self, class: &str, title: &str, wait: &Wait) -> Result<Hwnd> {
+        let what = format!("窗口 class={class:?} title={title:?}");
+        wait.until(&what, || {
+            let hwnd = self.FindWindow(Hwnd::NULL, "", 0, class, title, 0, 0)?;
+            Ok((!hwnd.is_null()).then_some(hwnd))
+        })
+    }
+}
@@ -0,0 +1,117 @@
+//! 窗口句柄及 `EnumWindow` 结果的解析
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::AoJia;
+use crate::bindings::Args;
+use crate::dispatch::{Arg, Call, Dispatch, FromValue, IntoArg, Value};
+use crate::error::{AoJiaError, ConversionError, ParseError, Result};
+
+/// 窗口句柄
+///
+/// 与进程 ID、坐标等同为 `i32`，单独成型以免误传。
+/// 不使用元组结构体，否则与插件文档中名为 `Hwnd` 的参数冲突。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Hwnd {
+    raw: i32,
+}
+
+impl Hwnd {
+    /// 空句柄，`FindWindow` 未找到时返回
+    pub const NULL: Hwnd = Hwnd::new(0);
+
+    pub const fn new(raw: i32) -> Self {
+        Self { raw }
+    }
+
+    pub fn is_null(self) -> bool {
+        self == Self::NULL
+    }
+
+    pub fn raw(self) -> i32 {
+        self.raw
+    }
+}
+
+impl From<i32> for Hwnd {
+    fn from(raw: i32) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl fmt::Display for Hwnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl FromStr for Hwnd {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.trim()
+            .parse()
+            .map(Hwnd::new)
+            .map_err(|_| ParseError::new("窗口句柄", s, "不是有效的整数"))
+    }
+}
+
+impl IntoArg for Hwnd {
+    fn into_arg(self) -> Arg {
+        Arg::input(self.raw)
+    }
+}
+
+impl FromValue for Hwnd {
+    fn from_value(value: &Value) -> std::result::Result<Self, ConversionError> {
+        value.to_i32().map(Hwnd::new)
+    }
+}
+
+/// 解析 `EnumWindow` 返回的句柄列表，句柄之间以 `|` 或 `,` 分隔
+///
+/// 空字符串表示没有窗口，其余情况下出现空项（如 `1||2`、末尾多余的分隔符）视为格式错误。
+pub fn parse_enum_window(raw: &str) -> std::result::Result<Vec<Hwnd>, ParseError> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    trimmed
+        .split(['|', ','])
+        .enumerate()
+        .map(|(i, s)| {
+            let err = |reason: String| ParseError::new("EnumWindow 结果", raw, reason);
+            if s.trim().is_empty() {
+                return Err(err(format!("第 {} 项为空", i + 1)));
+            }
+            s.parse::<Hwnd>()
+                .map_err(|_| err(format!("{s:?} 不是窗口句柄")))
+        })
+        .collect()
+}
+
+impl<B: Dispatch> AoJia<B> {
+    /// `EnumWindow` 的配套方法，返回句柄列表
+    #[allow(clippy::too_many_arguments)]
+    pub fn enum_window(
+        &self,
+        parent: Hwnd,
+        pro_name: &str,
+        pro_id: i32,
+        class: &str,
+        title: &str,
+        ty: i32,
+        flag: i32,
+        t: i32,
+    ) -> Result<Vec<Hwnd>> {
+        let raw = self.EnumWindow(parent, pro_name, pro_id, class, title, ty, flag, t)?;
+        parse_enum_window(&raw).map_err(|source| AoJiaError::Parse {
+            call: Call::new(
+                "EnumWindow",
+                &Args::EnumWindow(parent, pro_name, pro_id, class, title, ty, flag, t),
+            ),
+            source,
+        })
+    }
+}
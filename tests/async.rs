@@ -0,0 +1,107 @@
+#![cfg(feature = "async")]
+
+use std::sync::{Arc, Barrier};
+use std::time::Duration;
+
+use aojia::{AoJiaError, AsyncAoJia, Dispatch, MockAoJia, Point, Reply, Value};
+
+fn spawn_mock() -> (AsyncAoJia, Arc<MockAoJia>) {
+    let mock = Arc::new(MockAoJia::new());
+    let backend = Arc::clone(&mock);
+    (AsyncAoJia::spawn(move || Ok(backend)).unwrap(), mock)
+}
+
+#[tokio::test]
+async fn generated_methods_return_futures() {
+    let (aojia, mock) = spawn_mock();
+    mock.push_return("VerS", "1.0");
+    mock.push_outs("GetMousePos", 1, vec![Value::from(3), Value::from(4)]);
+
+    assert_eq!(aojia.VerS().await.unwrap(), "1.0");
+    let (mut x, mut y) = (0, 0);
+    aojia.GetMousePos(&mut x, &mut y, 0).await.unwrap();
+    assert_eq!((x, y), (3, 4));
+}
+
+#[tokio::test]
+async fn failure_codes_are_checked() {
+    let (aojia, mock) = spawn_mock();
+    mock.push_return("MoveTo", 0);
+    assert!(matches!(
+        aojia.MoveTo(1, 2).await,
+        Err(AoJiaError::Plugin { code: 0, .. })
+    ));
+}
+
+#[tokio::test]
+async fn run_executes_companion_methods_on_the_worker() {
+    let (aojia, mock) = spawn_mock();
+    mock.push_outs("GetMousePos", 1, vec![Value::from(5), Value::from(6)]);
+    let pos = aojia.run(|aojia| aojia.get_mouse_pos(0)).await.unwrap();
+    assert_eq!(pos, Point::new(5, 6));
+}
+
+/// 第一次调用 `YanShi` 时阻塞到测试放行
+struct Blocking {
+    gate: Arc<Barrier>,
+    mock: Arc<MockAoJia>,
+}
+
+impl Dispatch for Blocking {
+    fn invoke(&self, name: &str, args: &mut [aojia::Arg]) -> aojia::Result<Value> {
+        if name == "YanShi" {
+            self.gate.wait();
+        }
+        self.mock.invoke(name, args)
+    }
+}
+
+fn spawn_blocking() -> (AsyncAoJia, Arc<Barrier>, Arc<MockAoJia>) {
+    let gate = Arc::new(Barrier::new(2));
+    let mock = Arc::new(MockAoJia::new());
+    let backend = Blocking {
+        gate: Arc::clone(&gate),
+        mock: Arc::clone(&mock),
+    };
+    (AsyncAoJia::spawn(move || Ok(backend)).unwrap(), gate, mock)
+}
+
+#[tokio::test]
+async fn timeouts_report_the_call() {
+    let (aojia, gate, _mock) = spawn_blocking();
+    let err = aojia
+        .with_timeout(Duration::from_millis(20))
+        .YanShi(10, 20)
+        .await
+        .unwrap_err();
+    gate.wait();
+    match err {
+        AoJiaError::Timeout { call, timeout } => {
+            assert_eq!(call.to_string(), "YanShi(10, 20)");
+            assert_eq!(timeout, Duration::from_millis(20));
+        }
+        other => panic!("{other}"),
+    }
+}
+
+#[tokio::test]
+async fn dropped_futures_are_not_executed() {
+    let (aojia, gate, mock) = spawn_blocking();
+    mock.set_default("YanShi", Reply::ret(1))
+        .set_default("LeftClick", Reply::ret(1));
+    let busy = aojia.clone();
+    let first = tokio::spawn(async move { busy.YanShi(1, 1).await });
+    tokio::task::yield_now().await;
+
+    // 插件线程被占用期间排队的调用在放弃等待后不再执行
+    let impatient = aojia.with_timeout(Duration::from_millis(20));
+    assert!(matches!(
+        impatient.MoveTo(1, 1).await,
+        Err(AoJiaError::Timeout { .. })
+    ));
+
+    gate.wait();
+    first.await.unwrap().unwrap();
+    aojia.LeftClick().await.unwrap();
+    assert_eq!(mock.call_names(), ["YanShi", "LeftClick"]);
+}
@@ -0,0 +1,242 @@
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+use aojia::{
+    AoJia, AoJiaError, BindFlags, BindModes, Hwnd, KeyboardMode, MockAoJia, MouseMode, Point,
+    Reply, ScreenMode, Value,
+};
+
+#[test]
+fn modes_round_trip_through_plugin_strings() {
+    for &mode in ScreenMode::ALL {
+        assert_eq!(mode.as_str().parse::<ScreenMode>().unwrap(), mode);
+        assert_eq!(mode.to_string(), mode.as_str());
+    }
+    for &mode in KeyboardMode::ALL {
+        assert_eq!(mode.as_str().parse::<KeyboardMode>().unwrap(), mode);
+    }
+    for &mode in MouseMode::ALL {
+        assert_eq!(mode.as_str().parse::<MouseMode>().unwrap(), mode);
+    }
+    assert_eq!(" DX2 ".parse::<ScreenMode>().unwrap(), ScreenMode::Dx2);
+    assert_eq!(ScreenMode::default(), ScreenMode::Normal);
+}
+
+#[test]
+fn unknown_modes_are_rejected() {
+    let err = "dx3".parse::<ScreenMode>().unwrap_err();
+    assert_eq!(err.what, "屏幕模式");
+    assert!(err.reason.contains("normal"), "{err}");
+    assert!("gdi".parse::<KeyboardMode>().is_err());
+    assert!("".parse::<MouseMode>().is_err());
+    assert!(BindModes::parse("gdi", "windows", "dx9", "").is_err());
+}
+
+#[test]
+fn flags_round_trip_through_plugin_strings() {
+    let flags: BindFlags = "dx.public.input.ime| DX.PUBLIC.ACTIVE.API".parse().unwrap();
+    assert_eq!(flags, BindFlags::ACTIVE_API | BindFlags::INPUT_IME);
+    assert!(flags.contains(BindFlags::INPUT_IME));
+    assert!(flags.intersects(BindFlags::INPUT_IME | BindFlags::HIDE_DLL));
+    assert!(!flags.contains(BindFlags::INPUT_IME | BindFlags::HIDE_DLL));
+    // 按固定顺序输出
+    assert_eq!(
+        flags.to_string(),
+        "dx.public.active.api|dx.public.input.ime"
+    );
+
+    let mut all = BindFlags::empty();
+    for name in [
+        "dx.public.active.api",
+        "dx.public.active.message",
+        "dx.public.hide.dll",
+        "dx.public.graphic.protect",
+        "dx.public.input.ime",
+        "dx.public.disable.window.position",
+        "dx.public.disable.window.size",
+    ] {
+        let flag: BindFlags = name.parse().unwrap();
+        assert_eq!(flag.to_string(), name);
+        all |= flag;
+    }
+    assert_eq!(all.names().count(), 7);
+    assert_eq!(all.to_string().parse::<BindFlags>().unwrap(), all);
+
+    let none: BindFlags = "  ".parse().unwrap();
+    assert!(none.is_empty());
+    assert_eq!(none.to_string(), "");
+}
+
+#[test]
+fn unknown_flags_are_rejected() {
+    let err = "dx.public.hide.dll|dx.public.nope"
+        .parse::<BindFlags>()
+        .unwrap_err();
+    assert_eq!(err.what, "绑定标志");
+    assert!(err.reason.contains("dx.public.nope"), "{err}");
+    assert!(BindModes::parse("dx", "dx", "dx", "hide").is_err());
+}
+
+#[test]
+fn invalid_combinations_are_rejected_before_the_call() {
+    let mock = MockAoJia::new();
+    let aojia = AoJia::with_backend(&mock);
+    let gdi = BindModes::new(ScreenMode::Gdi, KeyboardMode::Windows, MouseMode::Windows);
+    let invalid = [
+        // 输入法需要 dx 键盘
+        BindModes::new(ScreenMode::Dx, KeyboardMode::Windows, MouseMode::Dx)
+            .flags(BindFlags::INPUT_IME),
+        // 保持激活需要 dx 键盘或鼠标
+        BindModes::new(ScreenMode::Dx, KeyboardMode::Windows, MouseMode::Windows2)
+            .flags(BindFlags::ACTIVE_API),
+        BindModes::new(ScreenMode::Dx2, KeyboardMode::Normal, MouseMode::Windows)
+            .flags(BindFlags::ACTIVE_MESSAGE),
+        // 截图保护需要 dx 截图
+        BindModes::new(ScreenMode::Gdi2, KeyboardMode::Dx, MouseMode::Dx)
+            .flags(BindFlags::GRAPHIC_PROTECT),
+        // 其余标志需要至少一项为 dx 模式
+        gdi.flags(BindFlags::HIDE_DLL),
+        gdi.flags(BindFlags::DISABLE_WINDOW_POSITION),
+        gdi.flags(BindFlags::DISABLE_WINDOW_SIZE),
+    ];
+    for modes in invalid {
+        assert!(modes.validate().is_err(), "{modes}");
+        let err = aojia.kq_hou_tai(Hwnd::new(9), &modes).unwrap_err();
+        assert!(matches!(err, AoJiaError::InvalidArgument { .. }), "{err}");
+    }
+    assert!(mock.calls().is_empty());
+
+    let valid = [
+        BindModes::new(ScreenMode::Gdi, KeyboardMode::Dx, MouseMode::Windows)
+            .flags(BindFlags::INPUT_IME | BindFlags::ACTIVE_API),
+        BindModes::new(ScreenMode::Dx, KeyboardMode::Windows, MouseMode::Windows)
+            .flags(BindFlags::GRAPHIC_PROTECT | BindFlags::HIDE_DLL),
+        BindModes::new(ScreenMode::Normal, KeyboardMode::Normal, MouseMode::Dx2)
+            .flags(BindFlags::ACTIVE_MESSAGE | BindFlags::DISABLE_WINDOW_SIZE),
+        gdi,
+    ];
+    for modes in valid {
+        modes.validate().unwrap();
+    }
+    let parsed = BindModes::parse("gdi", "dx", "windows", "dx.public.input.ime").unwrap();
+    assert_eq!(parsed, valid[0].flags(BindFlags::INPUT_IME));
+    assert!(BindModes::parse("gdi", "windows", "windows", "dx.public.input.ime").is_err());
+}
+
+#[test]
+fn kq_hou_tai_passes_modes_as_plugin_strings() {
+    let mock = MockAoJia::new();
+    mock.push_return("KQHouTai", 1);
+    let aojia = AoJia::with_backend(&mock);
+    let modes = BindModes::new(ScreenMode::Dx, KeyboardMode::Windows, MouseMode::Dx2)
+        .flags(BindFlags::HIDE_DLL | BindFlags::ACTIVE_API)
+        .ty(2);
+    aojia.kq_hou_tai(Hwnd::new(9), &modes).unwrap();
+    assert_eq!(
+        mock.calls()[0].inputs(),
+        [
+            &Value::from(9),
+            &Value::from("dx"),
+            &Value::from("windows"),
+            &Value::from("dx2"),
+            &Value::from("dx.public.active.api|dx.public.hide.dll"),
+            &Value::from(2),
+        ]
+    );
+}
+
+fn bound_mock() -> MockAoJia {
+    let mock = MockAoJia::new();
+    mock.set_default("KQHouTai", Reply::ret(1))
+        .set_default("GBHouTai", Reply::ret(1))
+        .set_default("LeftClick", Reply::ret(1));
+    mock
+}
+
+fn modes() -> BindModes {
+    BindModes::new(ScreenMode::Gdi, KeyboardMode::Windows, MouseMode::Windows)
+}
+
+fn unbinds(mock: &MockAoJia) -> usize {
+    mock.calls_to("GBHouTai").len()
+}
+
+#[test]
+fn guard_unbinds_on_drop() {
+    let mock = bound_mock();
+    let aojia = AoJia::with_backend(&mock);
+    {
+        let guard = aojia
+            .bind(Hwnd::new(1), &modes(), |e| panic!("{e}"))
+            .unwrap();
+        guard.left_click().unwrap();
+        assert_eq!(unbinds(&mock), 0);
+    }
+    assert_eq!(mock.call_names(), ["KQHouTai", "LeftClick", "GBHouTai"]);
+
+    // 手动解除后析构时不再解除
+    let guard = aojia
+        .bind(Hwnd::new(1), &modes(), |e| panic!("{e}"))
+        .unwrap();
+    guard.unbind().unwrap();
+    assert_eq!(unbinds(&mock), 2);
+}
+
+#[test]
+fn guard_unbinds_on_early_return() {
+    let mock = bound_mock();
+    mock.push_error("MoveTo", -1, "窗口已关闭");
+    let aojia = AoJia::with_backend(&mock);
+
+    let script = || -> aojia::Result<()> {
+        let guard = aojia.bind(Hwnd::new(1), &modes(), |e| panic!("{e}"))?;
+        guard.move_to(Point::new(1, 2))?;
+        guard.left_click()?;
+        Ok(())
+    };
+    assert!(script().is_err());
+    assert_eq!(mock.call_names(), ["KQHouTai", "MoveTo", "GBHouTai"]);
+}
+
+#[test]
+fn guard_unbinds_during_panic_unwind() {
+    let mock = bound_mock();
+    let aojia = AoJia::with_backend(&mock);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _guard = aojia
+            .bind(Hwnd::new(1), &modes(), |e| panic!("{e}"))
+            .unwrap();
+        panic!("脚本出错");
+    }));
+    assert!(result.is_err());
+    assert_eq!(unbinds(&mock), 1);
+}
+
+#[test]
+fn unbind_failures_reach_the_hook() {
+    let mock = bound_mock();
+    mock.push_error("GBHouTai", -7, "插件已卸载")
+        .push_return("GBHouTai", 0);
+    let aojia = AoJia::with_backend(&mock);
+    let errors = RefCell::new(Vec::new());
+
+    for _ in 0..2 {
+        aojia
+            .bind(Hwnd::new(1), &modes(), |e| errors.borrow_mut().push(e))
+            .unwrap();
+    }
+    let errors = errors.into_inner();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].hresult(), Some(-7));
+    assert_eq!(errors[0].call().unwrap().name, "GBHouTai");
+    assert!(matches!(errors[1], AoJiaError::Plugin { code: 0, .. }));
+
+    // 绑定失败时没有句柄，也就不会解除绑定
+    mock.push_return("KQHouTai", 0);
+    let err = aojia
+        .bind(Hwnd::new(1), &modes(), |e| panic!("{e}"))
+        .unwrap_err();
+    assert!(matches!(err, AoJiaError::Plugin { .. }));
+    assert_eq!(unbinds(&mock), 2);
+}
@@ -0,0 +1,63 @@
+use aojia::{ColorDelta, ColorSpec, Rgb};
+
+#[test]
+fn colors_round_trip() {
+    let rgb: Rgb = "1a2B3c".parse().unwrap();
+    assert_eq!(rgb, Rgb::new(0x1A, 0x2B, 0x3C));
+    assert_eq!(rgb.to_string(), "1A2B3C");
+    assert_eq!(rgb.to_string().parse::<Rgb>().unwrap(), rgb);
+
+    let delta: ColorDelta = "FF0000-101010".parse().unwrap();
+    assert_eq!(
+        delta,
+        ColorDelta::new(Rgb::new(0xFF, 0, 0), Rgb::new(16, 16, 16))
+    );
+    assert_eq!(delta.to_string(), "FF0000-101010");
+    // 省略偏色时写回为 000000
+    let exact: ColorDelta = "00FF00".parse().unwrap();
+    assert_eq!(exact, ColorDelta::exact(Rgb::new(0, 0xFF, 0)));
+    assert_eq!(exact.to_string(), "00FF00-000000");
+
+    for raw in ["FFFFFF-000000", "FF0000-101010|00FF00-000000|0000FF-050505"] {
+        let spec: ColorSpec = raw.parse().unwrap();
+        assert_eq!(spec.to_string(), raw);
+        assert_eq!(spec.to_string().parse::<ColorSpec>().unwrap(), spec);
+    }
+    let spec = ColorSpec::new(Rgb::WHITE).or(delta);
+    assert_eq!(spec.to_string(), "FFFFFF-000000|FF0000-101010");
+    assert_eq!(spec.colors().len(), 2);
+}
+
+#[test]
+fn matching_uses_the_delta_per_channel() {
+    let spec: ColorSpec = "808080-100000|000000".parse().unwrap();
+    assert!(spec.matches(Rgb::new(0x90, 0x80, 0x80)));
+    assert!(!spec.matches(Rgb::new(0x91, 0x80, 0x80)));
+    assert!(!spec.matches(Rgb::new(0x80, 0x81, 0x80)));
+    assert!(spec.matches(Rgb::BLACK));
+}
+
+#[test]
+fn malformed_colors_are_rejected() {
+    for raw in ["", "FFFFF", "FFFFFFF", "GG0000", "#FFFFF", "一二"] {
+        let err = raw.parse::<Rgb>().unwrap_err();
+        assert_eq!(err.what, "颜色", "{raw:?}");
+        assert_eq!(err.input, raw);
+    }
+    // 有 `-` 而缺少偏色，或缺少颜色
+    for raw in ["FF0000-", "-101010", "FF0000-10101", "FF0000-1010XY"] {
+        assert!(raw.parse::<ColorDelta>().is_err(), "{raw:?}");
+    }
+    for raw in [
+        "",
+        "  ",
+        "FF0000|",
+        "|FF0000",
+        "FF0000||00FF00",
+        "FF0000-|00FF00",
+    ] {
+        let err = raw.parse::<ColorSpec>().unwrap_err();
+        assert_eq!(err.what, "颜色描述", "{raw:?}");
+        assert_eq!(err.input, raw);
+    }
+}
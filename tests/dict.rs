@@ -0,0 +1,187 @@
+use aojia::{Bitmap, Dict, DictEncoding, DictEntry, Newline};
+
+/// 5x3 的横线，中间一行为文字
+fn dash() -> Bitmap {
+    Bitmap::from_fn(5, 3, |_, y| y == 1)
+}
+
+#[test]
+fn bitmap_is_column_major_hex() {
+    assert_eq!(dash().to_hex(), "4924");
+    let parsed = Bitmap::from_hex("4924", 3).unwrap();
+    assert_eq!(parsed, dash());
+    assert_eq!(
+        (parsed.width(), parsed.height(), parsed.points()),
+        (5, 3, 5)
+    );
+    assert_eq!(parsed.to_string(), ".....\n#####\n.....");
+    assert!(Bitmap::from_hex("49G4", 3).is_err());
+    assert!(Bitmap::from_hex("4924", 0).is_err());
+}
+
+#[test]
+fn short_bitmaps_take_their_width_from_the_point_count() {
+    let bar = Bitmap::from_fn(3, 1, |_, _| true);
+    assert_eq!(bar.to_hex(), "E");
+    // 补位可能是第 4 列
+    assert!(Bitmap::from_hex("E", 1).is_err());
+    assert_eq!(Bitmap::from_hex_with_points("E", 1, 3).unwrap(), bar);
+    assert!(Bitmap::from_hex_with_points("E", 1, 5).is_err());
+
+    let dict = Dict::parse(b"FFC$-$0.0.10$1\r\nC$.$0.0.2$1").unwrap();
+    assert_eq!(dict.get("-").unwrap().bitmap().width(), 10);
+    assert_eq!(dict.get(".").unwrap().bitmap().width(), 2);
+    assert!(Dict::parse(b"FFC$-$x$1").is_err());
+}
+
+#[test]
+fn bitmaps_of_every_size_round_trip() {
+    for height in 1..=9 {
+        for width in 1..=9 {
+            // 最后一列有点，否则与更窄的点阵无法区分
+            let bitmap = Bitmap::from_fn(width, height, |x, y| x == width - 1 || (x + y) % 3 == 0);
+            let mut dict = Dict::new();
+            dict.push(DictEntry::new("字", bitmap.clone()));
+            let parsed = Dict::parse(&dict.to_bytes().unwrap()).unwrap();
+            assert_eq!(
+                parsed.entries()[0].bitmap(),
+                &bitmap,
+                "{width}x{height}\n{bitmap}"
+            );
+        }
+    }
+}
+
+#[test]
+fn files_are_written_back_byte_identical() {
+    let files: [&[u8]; 9] = [
+        b"4924$-$0.0.5$3\r\nFFF$|$0.0.12$4\r\n",
+        b"4924$-$0.0.5$3\nFFF$|$0.0.12$4",
+        b"\xEF\xBB\xBF4924$\xE4\xB8\x80$0.0.5$3\r\n",
+        // 小写及补位不为 0 的点阵按原文写回
+        b"4925$-$0.0.5$3\nfff$|$1.2.3$4\r\n",
+        b"",
+        // 空行
+        b"\r\n4924$-$0.0.5$3\r\n\r\n  \r\nFFF$|$0.0.12$4\r\n\r\n",
+        b"4924$-$0.0.5$3\n\n\t",
+        b"\n\r\n",
+        b"4924$\xD2\xBB$0.0.5$3\r\n",
+    ];
+    for bytes in files {
+        let dict = Dict::parse(bytes).unwrap();
+        assert_eq!(
+            dict.to_bytes().unwrap(),
+            bytes,
+            "{}",
+            String::from_utf8_lossy(bytes)
+        );
+    }
+    let dict = Dict::parse(files[2]).unwrap();
+    assert_eq!(dict.entries()[0].ch(), "一");
+    assert_eq!(dict.entries()[0].bitmap(), &dash());
+    let dict = Dict::parse(files[5]).unwrap();
+    assert_eq!(dict.len(), 2);
+    assert!(Dict::parse(files[7]).unwrap().is_empty());
+}
+
+#[test]
+fn files_without_bom_are_gbk_unless_told_otherwise() {
+    // “一”的 GBK 编码 D2 BB 同时也是合法的 UTF-8
+    let gbk = b"4924$\xD2\xBB$0.0.5$3";
+    assert_eq!(Dict::parse(gbk).unwrap().entries()[0].ch(), "一");
+    let utf8 = Dict::parse_with(gbk, DictEncoding::Utf8).unwrap();
+    assert_eq!(utf8.entries()[0].ch(), "\u{4BB}");
+    assert_eq!(utf8.to_bytes().unwrap(), gbk);
+
+    let utf8 = "4924$一$0.0.5$3".as_bytes();
+    let dict = Dict::parse_with(utf8, DictEncoding::Utf8).unwrap();
+    assert_eq!(dict.entries()[0].ch(), "一");
+    assert_eq!(dict.to_bytes().unwrap(), utf8);
+}
+
+#[test]
+fn gbk_fixture_round_trips() {
+    // 按 Windows 下字库的格式手工生成：GBK 编码、\r\n 换行、大写点阵
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/dict_gbk.txt");
+    let bytes = std::fs::read(path).unwrap();
+    let dict = Dict::open(path).unwrap();
+    let chars: Vec<&str> = dict.entries().iter().map(DictEntry::ch).collect();
+    assert_eq!(chars, ["一", "十", "口", "日", "确定"]);
+    assert_eq!(dict.get("一").unwrap().bitmap(), &dash());
+    assert_eq!(
+        dict.get("口").unwrap().bitmap().to_string(),
+        "#####\n#...#\n#...#\n#...#\n#####"
+    );
+    for entry in dict.entries() {
+        assert_eq!(
+            entry.meta(),
+            format!("0.0.{}", entry.bitmap().points()),
+            "{}",
+            entry.ch()
+        );
+    }
+    assert_eq!(dict.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn entries_can_be_edited() {
+    let mut dict = Dict::parse(b"4924$-$0.0.5$3\nFFF$|$0.0.12$4").unwrap();
+    assert_eq!(dict.rename("-", "一"), 1);
+    assert!(dict.get("-").is_none());
+
+    let mut other = Dict::new();
+    other.push(DictEntry::new("横", dash()));
+    other.push(DictEntry::new(
+        "口",
+        Bitmap::from_fn(3, 3, |x, y| x != 1 || y != 1),
+    ));
+    // 点阵相同的“横”以原字库的“一”为准
+    assert_eq!(dict.merge(&other), 1);
+    assert_eq!(dict.len(), 3);
+    assert_eq!(dict.get("口").unwrap().meta(), "0.0.8");
+
+    assert_eq!(dict.remove("|"), 1);
+    // 沿用第一行的换行符，末尾原来没有换行
+    assert_eq!(
+        dict.to_bytes().unwrap(),
+        b"4924$\xD2\xBB$0.0.5$3\nF78$\xBF\xDA$0.0.8$3"
+    );
+
+    let entry = dict.get_mut("口").unwrap();
+    entry.set_bitmap(Bitmap::new(1, 3));
+    entry.set_meta("0.0.0");
+    assert_eq!(entry.to_string(), "0$口$0.0.0$3");
+}
+
+#[test]
+fn new_dicts_use_crlf() {
+    let mut dict = Dict::new();
+    dict.push(DictEntry::new("一", dash()));
+    assert_eq!(dict.to_bytes().unwrap(), b"4924$\xD2\xBB$0.0.5$3\r\n");
+
+    let mut dict = Dict::new()
+        .newline(Newline::Lf)
+        .encoding(DictEncoding::Utf8);
+    dict.push(DictEntry::new("一", dash()));
+    let bytes = dict.to_bytes().unwrap();
+    assert_eq!(bytes, "\u{FEFF}4924$一$0.0.5$3\n".as_bytes());
+    // 带 BOM，无需指定编码即可读回
+    assert_eq!(Dict::parse(&bytes).unwrap().to_bytes().unwrap(), bytes);
+
+    // GBK 无法表示的字
+    let mut dict = Dict::new();
+    dict.push(DictEntry::new("😀", dash()));
+    let err = dict.to_bytes().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(dict.clone().encoding(DictEncoding::Utf8).to_bytes().is_ok());
+}
+
+#[test]
+fn malformed_files_are_rejected() {
+    let err = Dict::parse(b"4924$-$0.0.5$3\n4924$-$3\n").unwrap_err();
+    assert!(err.reason.starts_with("第 2 行"), "{err}");
+    let err = Dict::parse_with(b"4924$\xBF\xDA$0.0.5$3\n", DictEncoding::Utf8).unwrap_err();
+    assert!(err.reason.contains("UTF-8"), "{err}");
+    let err = Dict::parse(b"4924$\xBF\xDA$0.0.5$3\n4924$\x81\x20$0.0.5$3").unwrap_err();
+    assert!(err.reason.starts_with("第 22 字节起不是 GBK"), "{err}");
+}
@@ -0,0 +1,153 @@
+use aojia::{
+    AoJia, AoJiaError, MockAoJia, PicMatch, PicResultFormat, Rgb, format_find_pic_ex,
+    parse_find_pic_ex,
+};
+
+const NAMES: &str = "a.bmp|b.bmp|c.bmp";
+
+fn pic(index: usize, name: &str, x: i32, y: i32) -> PicMatch {
+    PicMatch {
+        index,
+        name: name.to_string(),
+        x,
+        y,
+    }
+}
+
+#[test]
+fn empty_result_is_no_match() {
+    for format in [PicResultFormat::Index, PicResultFormat::Name] {
+        assert_eq!(parse_find_pic_ex("", format, NAMES).unwrap(), vec![]);
+        assert_eq!(parse_find_pic_ex("  ", format, NAMES).unwrap(), vec![]);
+    }
+}
+
+#[test]
+fn single_result_by_index() {
+    let matches = parse_find_pic_ex("1,100,200", PicResultFormat::Index, NAMES).unwrap();
+    assert_eq!(matches, vec![pic(1, "b.bmp", 100, 200)]);
+}
+
+#[test]
+fn single_result_by_name() {
+    let matches = parse_find_pic_ex("c.bmp,5,6", PicResultFormat::Name, NAMES).unwrap();
+    assert_eq!(matches, vec![pic(2, "c.bmp", 5, 6)]);
+}
+
+#[test]
+fn name_lookup_ignores_ascii_case() {
+    let matches = parse_find_pic_ex("A.BMP,5,6", PicResultFormat::Name, NAMES).unwrap();
+    assert_eq!(matches[0].index, 0);
+}
+
+#[test]
+fn multiple_results_keep_plugin_order() {
+    let matches = parse_find_pic_ex("2,1,1|0,10,20|2,-5,7", PicResultFormat::Index, NAMES).unwrap();
+    assert_eq!(
+        matches,
+        vec![
+            pic(2, "c.bmp", 1, 1),
+            pic(0, "a.bmp", 10, 20),
+            pic(2, "c.bmp", -5, 7)
+        ]
+    );
+}
+
+#[test]
+fn whitespace_around_fields_is_accepted() {
+    let matches = parse_find_pic_ex(" 0 , 1 , 2 \r\n", PicResultFormat::Index, NAMES).unwrap();
+    assert_eq!(matches, vec![pic(0, "a.bmp", 1, 2)]);
+}
+
+#[test]
+fn malformed_results_are_rejected() {
+    let cases = [
+        ("0,1", PicResultFormat::Index),
+        ("0,1,2,3", PicResultFormat::Index),
+        ("0,x,2", PicResultFormat::Index),
+        ("0,1,", PicResultFormat::Index),
+        ("-1,1,2", PicResultFormat::Index),
+        ("3,1,2", PicResultFormat::Index),
+        ("a.bmp,1,2", PicResultFormat::Index),
+        ("0,1,2|", PicResultFormat::Index),
+        ("0,1,2||1,3,4", PicResultFormat::Index),
+        ("d.bmp,1,2", PicResultFormat::Name),
+        ("a.bmp;1;2", PicResultFormat::Name),
+    ];
+    for (raw, format) in cases {
+        let err = parse_find_pic_ex(raw, format, NAMES).unwrap_err();
+        assert_eq!(err.input, raw);
+    }
+}
+
+#[test]
+fn index_without_pic_names_is_rejected() {
+    assert!(parse_find_pic_ex("0,1,2", PicResultFormat::Index, "").is_err());
+}
+
+#[test]
+fn format_round_trips() {
+    let matches = vec![pic(1, "b.bmp", 3, 4), pic(0, "a.bmp", 100, 200)];
+    for format in [PicResultFormat::Index, PicResultFormat::Name] {
+        let raw = format_find_pic_ex(&matches, format);
+        assert_eq!(parse_find_pic_ex(&raw, format, NAMES).unwrap(), matches);
+    }
+    assert_eq!(
+        format_find_pic_ex(&matches, PicResultFormat::Index),
+        "1,3,4|0,100,200"
+    );
+    assert_eq!(
+        format_find_pic_ex(&matches, PicResultFormat::Name),
+        "b.bmp,3,4|a.bmp,100,200"
+    );
+    assert_eq!(format_find_pic_ex(&[], PicResultFormat::Name), "");
+}
+
+#[test]
+fn find_pic_ex_parses_mock_output() {
+    let mock = MockAoJia::new();
+    let expected = vec![pic(2, "c.bmp", 7, 8)];
+    mock.push_return(
+        "FindPicEx",
+        format_find_pic_ex(&expected, PicResultFormat::Name),
+    );
+    mock.push_return("FindPicEx", "9,1,1");
+
+    let aojia = AoJia::with_backend(&mock);
+    let found = aojia
+        .find_pic_ex(
+            0,
+            0,
+            800,
+            600,
+            NAMES,
+            Rgb::BLACK,
+            0.9,
+            0,
+            0,
+            PicResultFormat::Name,
+        )
+        .unwrap();
+    assert_eq!(found, expected);
+    assert_eq!(mock.calls()[0].arg(9), &1.into());
+
+    let err = aojia
+        .find_pic_ex(
+            0,
+            0,
+            800,
+            600,
+            NAMES,
+            Rgb::BLACK,
+            0.9,
+            0,
+            0,
+            PicResultFormat::Index,
+        )
+        .unwrap_err();
+    let AoJiaError::Parse { call, .. } = err else {
+        panic!("{err}");
+    };
+    // 与实际发出的调用一致
+    assert_eq!(call, *mock.calls().last().unwrap());
+}
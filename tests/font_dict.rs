@@ -0,0 +1,87 @@
+#![cfg(feature = "font")]
+
+use aojia::{Dict, FontDict};
+
+/// Fira Mono Medium 的子集，以 SIL OFL 1.1 授权，见 `tests/fixtures/OFL.txt`
+fn font() -> FontDict {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/FiraMono-subset.ttf"
+    );
+    FontDict::open(path).unwrap_or_else(|e| panic!("无法读取测试字体 {path}：{e}"))
+}
+
+#[test]
+fn glyphs_are_trimmed_bitmaps() {
+    let font = font();
+    let font = font.size(20.0).antialias(false);
+
+    let bar = font.glyph('|').unwrap();
+    assert!(bar.height() > bar.width() * 3, "\n{bar}");
+    assert!((0..bar.width()).all(|x| bar.get(x, 0) && bar.get(x, bar.height() - 1)));
+
+    let dash = font.glyph('-').unwrap();
+    assert!(dash.width() > dash.height(), "\n{dash}");
+    assert!(font.glyph(' ').is_none());
+}
+
+#[test]
+fn embolden_and_threshold_change_the_strokes() {
+    let font = font();
+    let font = font.size(16.0);
+    let regular = font.glyph('l').unwrap();
+
+    let font = font.embolden(1);
+    let bold = font.glyph('l').unwrap();
+    assert_eq!(bold.width(), regular.width() + 1);
+    assert_eq!(bold.height(), regular.height());
+
+    let font = font.embolden(0);
+    let faint = font.threshold(0.01).glyph('l').unwrap();
+    assert!(faint.points() >= regular.points());
+}
+
+#[test]
+fn generated_dicts_round_trip() {
+    let font = font();
+    let font = font.size(14.0);
+    let dict = font.generate("OK OK!");
+    let chars: Vec<&str> = dict.entries().iter().map(|e| e.ch()).collect();
+    assert_eq!(chars, ["O", "K", "!"]);
+    let o = dict.get("O").unwrap();
+    assert_eq!(o.meta(), format!("0.0.{}", o.bitmap().points()));
+
+    let bytes = dict.to_bytes().unwrap();
+    let parsed = Dict::parse(&bytes).unwrap();
+    assert_eq!(parsed.to_bytes().unwrap(), bytes);
+    assert_eq!(
+        parsed.get("K").unwrap().bitmap(),
+        dict.get("K").unwrap().bitmap()
+    );
+
+    let mut dict = parsed;
+    assert_eq!(font.extend(&mut dict, "OKX"), 1);
+    assert_eq!(dict.len(), 4);
+}
+
+#[test]
+fn short_glyphs_survive_save_and_reload() {
+    for size in [10.0, 14.0, 20.0] {
+        let font = font().size(size).antialias(false);
+        let dict = font.generate("-_.");
+        assert_eq!(dict.len(), 3);
+        let parsed = Dict::parse(&dict.to_bytes().unwrap()).unwrap();
+        for (generated, reloaded) in dict.entries().iter().zip(parsed.entries()) {
+            assert_eq!(
+                reloaded.bitmap(),
+                generated.bitmap(),
+                "{} @ {size}\n{}",
+                generated.ch(),
+                generated.bitmap()
+            );
+        }
+    }
+    // 这些字高度不足 4，宽度只能由点数确定
+    let dash = font().size(14.0).antialias(false).glyph('-').unwrap();
+    assert!(dash.height() < 4, "\n{dash}");
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::thread;
+
+use aojia::{AoJia, AoJiaError, AoJiaHandle, Dispatch, MockAoJia, Point, Remote, Reply, Value};
+
+fn spawn_mock() -> (AoJiaHandle, Arc<MockAoJia>) {
+    let mock = Arc::new(MockAoJia::new());
+    let backend = Arc::clone(&mock);
+    let handle = AoJia::spawn(move || Ok(backend)).unwrap();
+    (handle, mock)
+}
+
+fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+#[test]
+fn handle_is_send_sync_and_clone() {
+    assert_send_sync::<AoJiaHandle>();
+    assert_send_sync::<Remote>();
+}
+
+#[test]
+fn calls_run_on_the_worker_thread() {
+    let caller = thread::current().id();
+    let remote = Remote::spawn(|| Ok(MockAoJia::new())).unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    assert!(remote.submit(Box::new(move |_| tx.send(thread::current().id()).unwrap())));
+    let worker = rx.recv().unwrap();
+    assert_ne!(worker, caller);
+}
+
+#[test]
+fn forwards_return_values_and_out_params() {
+    let (handle, mock) = spawn_mock();
+    mock.push_return("VerS", "1.0");
+    mock.push_outs("GetMousePos", 1, vec![Value::from(3), Value::from(4)]);
+
+    assert_eq!(handle.VerS().unwrap(), "1.0");
+    assert_eq!(handle.get_mouse_pos(0).unwrap(), Point::new(3, 4));
+    assert_eq!(mock.call_names(), ["VerS", "GetMousePos"]);
+}
+
+#[test]
+fn forwards_errors_with_the_original_call() {
+    let (handle, mock) = spawn_mock();
+    mock.push_error("MoveTo", -1, "失败");
+
+    let err = handle.MoveTo(1, 2).unwrap_err();
+    assert_eq!(err.hresult(), Some(-1));
+    assert_eq!(err.call().unwrap().to_string(), "MoveTo(1, 2)");
+
+    mock.set_missing("Ocr");
+    assert!(handle.resolve("Ocr").is_err());
+    assert!(handle.resolve("VerS").is_ok());
+}
+
+#[test]
+fn clones_share_one_backend_across_threads() {
+    let (handle, mock) = spawn_mock();
+    mock.set_default("MoveTo", Reply::ret(1));
+    let workers: Vec<_> = (0..4)
+        .map(|i| {
+            let handle = handle.clone();
+            thread::spawn(move || handle.MoveTo(i, i).unwrap())
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert_eq!(mock.calls_to("MoveTo").len(), 4);
+}
+
+#[test]
+fn factory_errors_are_returned_from_spawn() {
+    let err = AoJia::spawn(|| -> aojia::Result<MockAoJia> {
+        Err(AoJiaError::com(
+            aojia::Call::new("CoCreateInstance", &[]),
+            -2,
+            "未注册",
+        ))
+    })
+    .unwrap_err();
+    assert_eq!(err.hresult(), Some(-2));
+}
+
+struct Panicking;
+
+impl Dispatch for Panicking {
+    fn invoke(&self, name: &str, _args: &mut [aojia::Arg]) -> aojia::Result<Value> {
+        if name == "LeftClick" {
+            panic!("后端崩溃");
+        }
+        Ok(Value::I32(1))
+    }
+}
+
+#[test]
+fn a_panicking_call_fails_without_killing_the_worker() {
+    let handle = AoJia::spawn(|| Ok(Panicking)).unwrap();
+    assert!(handle.LeftClick().is_err());
+    assert_eq!(handle.MoveTo(1, 1).unwrap(), 1);
+}
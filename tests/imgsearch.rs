@@ -0,0 +1,167 @@
+use aojia::imgsearch::{find_pic, find_pic_ex, similarity_at, transparent_color};
+use aojia::{ImageError, PicSearch, Rect, Rgb, RgbImage, SearchDir, Transparency};
+
+const RED: Rgb = Rgb::new(0xFF, 0, 0);
+const GREEN: Rgb = Rgb::new(0, 0xFF, 0);
+
+/// 3x3 的图案，中心为绿色，其余为红色
+fn cross() -> RgbImage {
+    RgbImage::from_fn(3, 3, |x, y| if (x, y) == (1, 1) { GREEN } else { RED })
+}
+
+fn screen_with(pic: &RgbImage, points: &[(i32, i32)]) -> RgbImage {
+    let mut screen = RgbImage::filled(20, 10, Rgb::WHITE);
+    for &(x, y) in points {
+        screen.draw(pic, x, y);
+    }
+    screen
+}
+
+#[test]
+fn finds_exact_match() {
+    let screen = screen_with(&cross(), &[(5, 4)]);
+    let search = PicSearch::new(screen.rect(), ["cross.bmp"]).sim(1.0);
+    let found = find_pic(&screen, &[cross()], &search).unwrap();
+    assert_eq!((found.index, found.name.as_str()), (0, "cross.bmp"));
+    assert_eq!((found.x, found.y), (5, 4));
+}
+
+#[test]
+fn delta_tolerates_channel_differences() {
+    let shaded = RgbImage::from_fn(3, 3, |x, y| {
+        if (x, y) == (1, 1) {
+            Rgb::new(0x10, 0xF0, 0x08)
+        } else {
+            Rgb::new(0xF0, 0x08, 0x10)
+        }
+    });
+    let screen = screen_with(&shaded, &[(2, 2)]);
+    let search = PicSearch::new(screen.rect(), ["cross.bmp"]).sim(1.0);
+    assert!(find_pic(&screen, &[cross()], &search).is_none());
+
+    let found = find_pic(
+        &screen,
+        &[cross()],
+        &search.delta(Rgb::new(0x10, 0x10, 0x10)),
+    );
+    assert_eq!(found.unwrap().point(), aojia::Point::new(2, 2));
+}
+
+#[test]
+fn sim_allows_differing_points() {
+    let mut damaged = cross();
+    damaged.set(0, 0, Rgb::BLACK);
+    let screen = screen_with(&damaged, &[(1, 1)]);
+    assert!(
+        (similarity_at(&screen, &cross(), 1, 1, Rgb::BLACK, Transparency::Opaque) - 8.0 / 9.0)
+            .abs()
+            < 1e-9
+    );
+
+    let search = PicSearch::new(screen.rect(), ["cross.bmp"]).transparency(Transparency::Opaque);
+    assert!(find_pic(&screen, &[cross()], &search.clone().sim(0.9)).is_none());
+    assert!(find_pic(&screen, &[cross()], &search.sim(0.85)).is_some());
+}
+
+#[test]
+fn uniform_corners_are_transparent() {
+    assert_eq!(
+        transparent_color(&cross(), Transparency::Corners),
+        Some(RED)
+    );
+    assert_eq!(transparent_color(&cross(), Transparency::Opaque), None);
+
+    // 红色部分被视为透明，只需中心的绿点相符
+    let mut screen = RgbImage::filled(20, 10, Rgb::WHITE);
+    screen.set(7, 3, GREEN);
+    let search = PicSearch::new(screen.rect(), ["cross.bmp"]).sim(1.0);
+    assert_eq!(
+        find_pic(&screen, &[cross()], &search).unwrap().point(),
+        aojia::Point::new(6, 2)
+    );
+    let opaque = search.transparency(Transparency::Opaque);
+    assert!(find_pic(&screen, &[cross()], &opaque).is_none());
+}
+
+#[test]
+fn dir_controls_which_match_comes_first() {
+    let screen = screen_with(&cross(), &[(1, 1), (15, 1), (1, 6), (15, 6)]);
+    let search = PicSearch::new(screen.rect(), ["cross.bmp"])
+        .sim(1.0)
+        .transparency(Transparency::Opaque);
+    let first = |dir| {
+        find_pic(&screen, &[cross()], &search.clone().dir(dir))
+            .unwrap()
+            .point()
+    };
+    assert_eq!(first(SearchDir::LeftTop), aojia::Point::new(1, 1));
+    assert_eq!(first(SearchDir::RightTop), aojia::Point::new(15, 1));
+    assert_eq!(first(SearchDir::LeftBottom), aojia::Point::new(1, 6));
+    assert_eq!(first(SearchDir::RightBottom), aojia::Point::new(15, 6));
+}
+
+#[test]
+fn picture_must_fit_inside_region() {
+    let screen = screen_with(&cross(), &[(5, 4)]);
+    let search = |region| PicSearch::new(region, ["cross.bmp"]).sim(1.0);
+    assert!(find_pic(&screen, &[cross()], &search(Rect::new(5, 4, 8, 7))).is_some());
+    // 右下边界不含在区域内
+    assert!(find_pic(&screen, &[cross()], &search(Rect::new(5, 4, 7, 7))).is_none());
+    // 超出屏幕的区域被裁掉
+    assert!(
+        find_pic(
+            &screen,
+            &[cross()],
+            &search(Rect::new(-100, -100, 100, 100))
+        )
+        .is_some()
+    );
+}
+
+#[test]
+fn find_pic_ex_reports_every_picture_in_order() {
+    let other = RgbImage::filled(2, 2, GREEN);
+    let mut screen = screen_with(&cross(), &[(1, 1), (10, 5)]);
+    screen.draw(&other, 16, 0);
+    let search = PicSearch::new(screen.rect(), ["cross.bmp", "green.bmp"])
+        .sim(1.0)
+        .transparency(Transparency::Opaque);
+    let pics = [cross(), other];
+
+    let found: Vec<_> = find_pic_ex(&screen, &pics, &search)
+        .into_iter()
+        .map(|m| (m.index, m.name, m.x, m.y))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (0, "cross.bmp".to_string(), 1, 1),
+            (0, "cross.bmp".to_string(), 10, 5),
+            (1, "green.bmp".to_string(), 16, 0),
+        ]
+    );
+    // FindPic 按图片顺序，第一张找到即返回
+    assert_eq!(find_pic(&screen, &pics, &search).unwrap().index, 0);
+}
+
+#[test]
+fn bmp_round_trip() {
+    let image = RgbImage::from_fn(5, 3, |x, y| Rgb::new(x as u8 * 40, y as u8 * 80, 7));
+    let bytes = image.to_bmp();
+    assert_eq!(RgbImage::decode(&bytes).unwrap(), image);
+    assert!(RgbImage::decode(b"GIF89a").is_err());
+    assert!(RgbImage::from_bmp(&bytes[..60]).is_err());
+}
+
+#[test]
+fn oversized_bmp_headers_are_rejected() {
+    // 宽高均为 0x7fffffff，在 32 位目标上所需字节数会溢出
+    let mut bytes = RgbImage::filled(2, 2, RED).to_bmp();
+    bytes[18..22].copy_from_slice(&0x7fff_ffff_u32.to_le_bytes());
+    bytes[22..26].copy_from_slice(&0x7fff_ffff_u32.to_le_bytes());
+    for bits in [24u16, 32] {
+        bytes[28..30].copy_from_slice(&bits.to_le_bytes());
+        let err = RgbImage::from_bmp(&bytes).unwrap_err();
+        assert!(matches!(err, ImageError::Format(_)), "{err}");
+    }
+}
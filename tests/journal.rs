@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use aojia::{Arg, Dispatch, Journal, JournalEntry, JournalError, MockAoJia, Reply, Value};
+
+/// 每个测试使用单独的目录，返回其中的日志路径
+fn journal_path(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aojia-journal-{}-{test}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join("calls.jsonl")
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    PathBuf::from(format!("{}.{n}", path.display()))
+}
+
+/// 日志文件中依次记录的方法名，文件不存在时为 `None`
+fn methods(path: &Path) -> Option<Vec<String>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(
+        text.lines()
+            .map(|line| serde_json::from_str::<JournalEntry>(line).unwrap().method)
+            .collect(),
+    )
+}
+
+fn entries(path: &Path) -> Vec<JournalEntry> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn call_all(journal: &Journal<MockAoJia>, names: &[&str]) {
+    for name in names {
+        journal.backend().set_default(name, Reply::ret(1));
+        journal.invoke(name, &mut [Arg::input(1)]).unwrap();
+    }
+    assert!(journal.take_error().is_none());
+}
+
+#[test]
+fn old_files_shift_and_the_oldest_is_dropped() {
+    let path = journal_path("shift");
+    // 每行都超过上限，因此每次写入前都会轮换
+    let journal = Journal::open(MockAoJia::new(), &path).unwrap().rotate(1, 2);
+    call_all(&journal, &["A", "B", "C", "D"]);
+
+    assert_eq!(methods(&path).unwrap(), ["D"]);
+    assert_eq!(methods(&rotated(&path, 1)).unwrap(), ["C"]);
+    assert_eq!(methods(&rotated(&path, 2)).unwrap(), ["B"]);
+    assert_eq!(methods(&rotated(&path, 3)), None);
+}
+
+#[test]
+fn keep_zero_discards_the_full_file() {
+    let path = journal_path("keep0");
+    let journal = Journal::open(MockAoJia::new(), &path).unwrap().rotate(1, 0);
+    call_all(&journal, &["A", "B", "C"]);
+
+    assert_eq!(methods(&path).unwrap(), ["C"]);
+    assert_eq!(methods(&rotated(&path, 1)), None);
+}
+
+#[test]
+fn rotation_is_triggered_by_size() {
+    let path = journal_path("size");
+    // 先量出一行的长度
+    let journal = Journal::open(MockAoJia::new(), &path).unwrap();
+    call_all(&journal, &["A"]);
+    let line = std::fs::metadata(&path).unwrap().len();
+    drop(journal);
+
+    // 上限可容纳两行，重新打开时已有一行
+    let max_bytes = line * 2 + 10;
+    let journal = Journal::open(MockAoJia::new(), &path)
+        .unwrap()
+        .rotate(max_bytes, 3);
+    call_all(&journal, &["B"]);
+    assert_eq!(methods(&path).unwrap(), ["A", "B"]);
+    assert_eq!(methods(&rotated(&path, 1)), None);
+
+    call_all(&journal, &["C", "D", "E"]);
+    assert_eq!(methods(&path).unwrap(), ["E"]);
+    assert_eq!(methods(&rotated(&path, 1)).unwrap(), ["C", "D"]);
+    assert_eq!(methods(&rotated(&path, 2)).unwrap(), ["A", "B"]);
+    for file in [path.clone(), rotated(&path, 1), rotated(&path, 2)] {
+        assert!(std::fs::metadata(&file).unwrap().len() <= max_bytes);
+    }
+
+    // 不超过上限时从不轮换
+    let path = journal_path("unlimited");
+    let journal = Journal::open(MockAoJia::new(), &path)
+        .unwrap()
+        .rotate(u64::MAX, 3);
+    call_all(&journal, &["A", "B", "C"]);
+    assert_eq!(methods(&path).unwrap(), ["A", "B", "C"]);
+    assert_eq!(methods(&rotated(&path, 1)), None);
+}
+
+#[test]
+fn entries_record_arguments_outputs_and_return() {
+    let path = journal_path("content");
+    let mock = MockAoJia::new();
+    mock.push_outs("Query", 3, ["a.bmp", "x"]);
+    let journal = Journal::open(mock, &path).unwrap();
+
+    let before = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let start = Instant::now();
+    let mut args = [Arg::input(7), Arg::out(), Arg::input("b"), Arg::out()];
+    assert_eq!(journal.invoke("Query", &mut args).unwrap(), Value::from(3));
+    let elapsed = start.elapsed();
+    assert!(journal.take_error().is_none());
+
+    let [entry] = entries(&path).try_into().unwrap();
+    assert_eq!(entry.method, "Query");
+    assert_eq!(entry.args, [Value::from(7), Value::from("b")]);
+    assert_eq!(entry.outs, [Value::from("a.bmp"), Value::from("x")]);
+    assert_eq!(entry.ret, Value::from(3));
+    assert_eq!((entry.hresult, entry.error, entry.kind), (0, None, None));
+    assert!(entry.ts >= before.as_millis() as u64, "{}", entry.ts);
+    assert!(entry.duration_us <= elapsed.as_micros() as u64);
+}
+
+#[test]
+fn redaction_applies_to_arguments_and_outputs() {
+    let path = journal_path("redact");
+    let mock = MockAoJia::new();
+    mock.push_outs("Login", 1, ["token"])
+        .push_return("Other", 1);
+    // 参数位置按全部参数计算，传址参数也在其中
+    let journal = Journal::open(mock, &path)
+        .unwrap()
+        .redact(|method, i, _| (method == "Login" && i >= 1).then(|| Value::from("***")));
+
+    let mut args = [Arg::input("user"), Arg::input("secret"), Arg::out()];
+    journal.invoke("Login", &mut args).unwrap();
+    // 遮盖只影响日志，调用方拿到原值
+    assert_eq!(args[2].value(), &Value::from("token"));
+    journal
+        .invoke("Other", &mut [Arg::input("a"), Arg::input("b")])
+        .unwrap();
+
+    let [login, other] = entries(&path).try_into().unwrap();
+    assert_eq!(login.args, [Value::from("user"), Value::from("***")]);
+    assert_eq!(login.outs, [Value::from("***")]);
+    assert_eq!(other.args, [Value::from("a"), Value::from("b")]);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        !text.contains("secret") && !text.contains("token"),
+        "{text}"
+    );
+}
+
+#[test]
+fn failed_calls_record_the_error() {
+    let path = journal_path("error");
+    let mock = MockAoJia::new();
+    mock.push_error("SetPath", -5, "路径不存在");
+    let journal = Journal::open(mock, &path)
+        .unwrap()
+        .redact(|_, _, _| Some(Value::from("***")));
+
+    let err = journal
+        .invoke("SetPath", &mut [Arg::input("C:/secret")])
+        .unwrap_err();
+    assert_eq!(err.hresult(), Some(-5));
+
+    let [entry] = entries(&path).try_into().unwrap();
+    assert_eq!(entry.args, [Value::from("***")]);
+    assert_eq!(entry.ret, Value::Empty);
+    assert_eq!(entry.hresult, -5);
+    assert_eq!(entry.error.as_deref(), Some("路径不存在"));
+    assert_eq!(entry.kind, Some(JournalError::Com));
+    // 错误信息中的调用参数未经遮盖，不写入日志
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(!text.contains("secret"), "{text}");
+    assert!(text.contains("\"ret\":null"), "{text}");
+}
+
+#[test]
+fn non_finite_floats_are_read_back_as_empty() {
+    let path = journal_path("nan");
+    let mock = MockAoJia::new();
+    mock.push_return("Sim", f64::NAN);
+    let journal = Journal::open(mock, &path).unwrap();
+
+    let mut args = [Arg::input(f64::INFINITY), Arg::input(0.5)];
+    let ret = journal.invoke("Sim", &mut args).unwrap();
+    assert!(ret.to_f64().unwrap().is_nan());
+
+    let [entry] = entries(&path).try_into().unwrap();
+    assert_eq!(entry.args, [Value::Empty, Value::from(0.5)]);
+    assert_eq!(entry.ret, Value::Empty);
+}
@@ -0,0 +1,102 @@
+use aojia::{Arg, Dispatch, MockAoJia, Reply, Value};
+
+fn call(mock: &MockAoJia, name: &str, args: &mut [Arg]) -> aojia::Result<Value> {
+    mock.invoke(name, args)
+}
+
+#[test]
+fn replies_are_queued_per_method_in_order() {
+    let mock = MockAoJia::new();
+    mock.push_return("VerS", "1")
+        .push_return("GetColor", "FFFFFF")
+        .push_return("VerS", "2");
+    assert_eq!(mock.pending(), 3);
+
+    assert_eq!(call(&mock, "VerS", &mut []).unwrap(), Value::from("1"));
+    assert_eq!(call(&mock, "VerS", &mut []).unwrap(), Value::from("2"));
+    assert_eq!(mock.pending(), 1);
+    assert_eq!(
+        call(&mock, "GetColor", &mut []).unwrap(),
+        Value::from("FFFFFF")
+    );
+    assert_eq!(mock.pending(), 0);
+    // 队列耗尽且没有默认应答时报错，不当作成功
+    let err = call(&mock, "VerS", &mut []).unwrap_err();
+    assert_eq!(err.hresult(), Some(0x8000_FFFF_u32 as i32));
+    assert!(err.to_string().contains("VerS"), "{err}");
+    assert_eq!(mock.call_names(), ["VerS", "VerS", "GetColor", "VerS"]);
+
+    // 需要空返回值时显式设置
+    mock.set_default("VerS", Reply::ret(Value::Empty));
+    assert_eq!(call(&mock, "VerS", &mut []).unwrap(), Value::Empty);
+}
+
+#[test]
+fn defaults_answer_after_the_queue() {
+    let mock = MockAoJia::new();
+    mock.set_default("IsBind", Reply::ret(1))
+        .push_return("IsBind", 0);
+    assert_eq!(call(&mock, "IsBind", &mut []).unwrap(), Value::from(0));
+    assert_eq!(call(&mock, "IsBind", &mut []).unwrap(), Value::from(1));
+    assert_eq!(call(&mock, "IsBind", &mut []).unwrap(), Value::from(1));
+    // 默认应答不计入待消耗的应答
+    assert_eq!(mock.pending(), 0);
+}
+
+#[test]
+fn outs_fill_out_slots_in_order() {
+    let mock = MockAoJia::new();
+    mock.push_outs("GetMousePos", 1, [Value::from(3), Value::from(4)]);
+    let mut args = [Arg::input(0), Arg::out(), Arg::out()];
+    assert_eq!(
+        call(&mock, "GetMousePos", &mut args).unwrap(),
+        Value::from(1)
+    );
+    assert_eq!(
+        args,
+        [Arg::input(0), Arg::Out(3.into()), Arg::Out(4.into())]
+    );
+
+    // 回写值多于传址参数时报错
+    mock.push_outs("GetMousePos", 1, [1, 2, 3]);
+    let mut args = [Arg::input(0), Arg::out(), Arg::out()];
+    let err = call(&mock, "GetMousePos", &mut args).unwrap_err();
+    assert_eq!(err.hresult(), Some(0x8002_000E_u32 as i32), "{err}");
+}
+
+#[test]
+fn failures_and_missing_methods_are_com_errors() {
+    let mock = MockAoJia::new();
+    mock.push_error("MoveTo", -5, "窗口已关闭")
+        .set_missing("KQHouTai");
+
+    let err = call(&mock, "MoveTo", &mut [Arg::input(1), Arg::input(2)]).unwrap_err();
+    assert_eq!(err.hresult(), Some(-5));
+    assert_eq!(
+        err.call().unwrap().inputs(),
+        [&Value::from(1), &Value::from(2)]
+    );
+
+    let err = call(&mock, "KQHouTai", &mut []).unwrap_err();
+    assert_eq!(err.hresult(), Some(0x8002_0006_u32 as i32));
+    assert!(mock.resolve("KQHouTai").is_err());
+    assert!(mock.resolve("MoveTo").is_ok());
+}
+
+#[test]
+fn calls_are_recorded_until_taken() {
+    let mock = MockAoJia::new();
+    mock.set_missing("Missing")
+        .set_default("MoveTo", Reply::ret(1));
+    call(&mock, "MoveTo", &mut [Arg::input(1), Arg::input(2)]).unwrap();
+    call(&mock, "Missing", &mut []).unwrap_err();
+    call(&mock, "MoveTo", &mut [Arg::input(3), Arg::input(4)]).unwrap();
+
+    // 失败的调用同样被记录
+    assert_eq!(mock.call_names(), ["MoveTo", "Missing", "MoveTo"]);
+    assert_eq!(mock.calls_to("MoveTo")[1].arg(0), &Value::from(3));
+
+    let taken = mock.take_calls();
+    assert_eq!(taken.len(), 3);
+    assert!(mock.calls().is_empty());
+}
@@ -0,0 +1,168 @@
+use aojia::{
+    AoJia, AoJiaError, ColorSpec, Glyph, MockAoJia, OcrColorType, OcrDictScope, OcrLine, OcrLines,
+    OcrOutput, OcrRequest, OcrResult, Point, Rect, Rgb, Value, format_ocr, parse_ocr,
+};
+
+fn glyph(ch: &str, x: i32, y: i32) -> Glyph {
+    Glyph {
+        ch: ch.to_string(),
+        x,
+        y,
+    }
+}
+
+fn texts(result: &OcrResult) -> Vec<&str> {
+    result.lines.iter().map(|l| l.text.as_str()).collect()
+}
+
+#[test]
+fn text_output_in_single_and_multi_lines() {
+    let single = parse_ocr("确定|取消", OcrOutput::Text, OcrLines::Single, "").unwrap();
+    assert_eq!(texts(&single), ["确定|取消"]);
+    assert!(single.lines[0].glyphs.is_empty());
+    assert_eq!(single.find_text("确定"), None);
+
+    let multi = parse_ocr("第一行\r\n第二行", OcrOutput::Text, OcrLines::Multi, "").unwrap();
+    assert_eq!(texts(&multi), ["第一行", "第二行"]);
+    assert_eq!(multi.text(), "第一行\n第二行");
+
+    let custom = parse_ocr("a#b##c", OcrOutput::Text, OcrLines::Multi, "#").unwrap();
+    assert_eq!(texts(&custom), ["a", "b", "", "c"]);
+}
+
+#[test]
+fn positions_in_single_and_multi_lines() {
+    let single = parse_ocr(
+        "确,10,20|定,22,20",
+        OcrOutput::Positions,
+        OcrLines::Single,
+        "",
+    )
+    .unwrap();
+    assert_eq!(
+        single.lines,
+        [OcrLine::from_glyphs(vec![
+            glyph("确", 10, 20),
+            glyph("定", 22, 20)
+        ])]
+    );
+
+    let multi = parse_ocr(
+        "a,1,2|b,3,2\nc,1,9",
+        OcrOutput::Positions,
+        OcrLines::Multi,
+        "",
+    )
+    .unwrap();
+    assert_eq!(texts(&multi), ["ab", "c"]);
+    assert_eq!(multi.glyphs().count(), 3);
+    assert_eq!(multi.find_text("c"), Some(Point::new(1, 9)));
+    // 文本不能跨行
+    assert_eq!(multi.find_text("bc"), None);
+
+    let custom = parse_ocr("a,1,2@@b,3,4", OcrOutput::Positions, OcrLines::Multi, "@@").unwrap();
+    assert_eq!(texts(&custom), ["a", "b"]);
+}
+
+#[test]
+fn empty_output_has_no_lines() {
+    for output in [OcrOutput::Text, OcrOutput::Positions] {
+        for lines in [OcrLines::Single, OcrLines::Multi] {
+            let result = parse_ocr("", output, lines, "").unwrap();
+            assert!(result.is_empty());
+            assert!(result.lines.is_empty());
+            assert_eq!(format_ocr(&result, output, lines, ""), "");
+        }
+    }
+}
+
+#[test]
+fn glyphs_may_contain_separators() {
+    let raw = "|,1,2|a,3,4|,,5,6|ab,7,8|x|y,9,10";
+    let result = parse_ocr(raw, OcrOutput::Positions, OcrLines::Single, "").unwrap();
+    assert_eq!(
+        result.lines[0].glyphs,
+        [
+            glyph("|", 1, 2),
+            glyph("a", 3, 4),
+            glyph(",", 5, 6),
+            glyph("ab", 7, 8),
+            glyph("x|y", 9, 10),
+        ]
+    );
+    assert_eq!(
+        format_ocr(&result, OcrOutput::Positions, OcrLines::Single, ""),
+        raw
+    );
+}
+
+#[test]
+fn find_text_matches_on_glyph_boundaries() {
+    let line = OcrLine::from_glyphs(vec![
+        glyph("ab", 1, 1),
+        glyph("确定", 10, 1),
+        glyph("b", 30, 1),
+        glyph("确", 40, 1),
+    ]);
+    assert_eq!(line.text, "ab确定b确");
+    assert_eq!(line.find_text("确定"), Some(Point::new(10, 1)));
+    assert_eq!(line.find_text("b确"), Some(Point::new(30, 1)));
+    // 从多字符字的中间开始不算
+    assert_eq!(line.find_text("定"), None);
+    assert_eq!(line.find_text("ab确定b确x"), None);
+    assert_eq!(line.find_text(""), None);
+}
+
+#[test]
+fn malformed_positions_are_rejected() {
+    for raw in ["a,1,2|", "a,1", "a,1,2|b,x,3", ",1,2", "a,1|b,2,3", "abc"] {
+        let err = parse_ocr(raw, OcrOutput::Positions, OcrLines::Single, "").unwrap_err();
+        assert_eq!(err.input, raw, "{err}");
+    }
+}
+
+#[test]
+fn modes_convert_from_plugin_integers() {
+    for ty in [OcrColorType::Text, OcrColorType::Background] {
+        assert_eq!(OcrColorType::try_from(ty.type_c()).unwrap(), ty);
+    }
+    for scope in [OcrDictScope::Current, OcrDictScope::All] {
+        assert_eq!(OcrDictScope::try_from(scope.type_d()).unwrap(), scope);
+    }
+    for output in [OcrOutput::Text, OcrOutput::Positions] {
+        assert_eq!(OcrOutput::try_from(output.type_r()).unwrap(), output);
+    }
+    for lines in [OcrLines::Single, OcrLines::Multi] {
+        assert_eq!(OcrLines::try_from(lines.type_t()).unwrap(), lines);
+    }
+
+    let err = OcrOutput::try_from(2).unwrap_err();
+    assert_eq!((err.what, err.input.as_str()), ("TypeR", "2"));
+    assert!(OcrColorType::try_from(-1).is_err());
+    assert!(OcrDictScope::try_from(2).is_err());
+    assert!(OcrLines::try_from(7).is_err());
+}
+
+#[test]
+fn requests_send_the_arguments_they_describe() {
+    let mock = MockAoJia::new();
+    mock.push_return("Ocr", "确,1,2|定,5,2")
+        .push_return("Ocr", "确,1");
+    let aojia = AoJia::with_backend(&mock);
+    let request = OcrRequest::new(Rect::new(0, 0, 80, 20), ColorSpec::new(Rgb::WHITE))
+        .text("确定")
+        .output(OcrOutput::Positions)
+        .lines(OcrLines::Multi)
+        .pic_name("a.bmp");
+
+    let result = request.run(&aojia).unwrap();
+    assert_eq!(texts(&result), ["确定"]);
+    assert_eq!(mock.calls()[0], request.call());
+    assert_eq!(request.call().arg(9), &Value::from(1));
+
+    let err = request.run(&aojia).unwrap_err();
+    let AoJiaError::Parse { call, .. } = err else {
+        panic!("{err}");
+    };
+    assert_eq!(call, mock.calls()[1]);
+}
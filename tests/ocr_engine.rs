@@ -0,0 +1,141 @@
+use aojia::{
+    AoJia, Bitmap, Dict, DictEntry, Frame, OcrColorType, OcrDictScope, OcrEngine, OcrLines,
+    OcrOutput, OcrRequest, Point, Rect, Rgb, RgbImage, VirtualScreen,
+};
+
+/// 3x5 的“一”“口”“十”
+fn dict() -> Dict {
+    let mut dict = Dict::new();
+    dict.push(DictEntry::new("一", Bitmap::from_fn(3, 5, |_, y| y == 2)));
+    dict.push(DictEntry::new(
+        "口",
+        Bitmap::from_fn(3, 5, |x, y| x != 1 || y == 0 || y == 4),
+    ));
+    dict.push(DictEntry::new(
+        "十",
+        Bitmap::from_fn(3, 5, |x, y| x == 1 || y == 2),
+    ));
+    dict
+}
+
+/// 以 `ink` 在白底上于 `(x, y)` 处画出字库中的字
+fn draw(image: &mut RgbImage, dict: &Dict, ch: &str, x: u32, y: u32, ink: Rgb) {
+    let bitmap = dict.get(ch).unwrap().bitmap();
+    for bx in 0..bitmap.width() {
+        for by in 0..bitmap.height() {
+            if bitmap.get(bx, by) {
+                image.set(x + bx, y + by, ink);
+            }
+        }
+    }
+}
+
+fn engine() -> OcrEngine {
+    let mut engine = OcrEngine::new();
+    engine.load(0, dict());
+    engine
+}
+
+fn whole() -> Rect {
+    Rect::new(0, 0, 30, 20)
+}
+
+#[test]
+fn single_and_multi_lines() {
+    let dict = dict();
+    let mut image = RgbImage::filled(30, 20, Rgb::WHITE);
+    draw(&mut image, &dict, "十", 9, 2, Rgb::BLACK);
+    draw(&mut image, &dict, "口", 2, 2, Rgb::BLACK);
+    draw(&mut image, &dict, "一", 5, 11, Rgb::BLACK);
+    let engine = engine();
+
+    let request = OcrRequest::new(whole(), Rgb::BLACK).sim(1.0);
+    let result = engine.recognize(&image, &request);
+    assert_eq!(result.text(), "口一十");
+    assert_eq!(result.find_text("十"), Some(Point::new(9, 2)));
+
+    let multi = request.lines(OcrLines::Multi);
+    let result = engine.recognize(&image, &multi);
+    assert_eq!(result.text(), "口十\n一");
+
+    // 区域外的字不识别，坐标仍为屏幕坐标
+    let region = OcrRequest::new(Rect::new(4, 8, 30, 20), Rgb::BLACK);
+    let result = engine.recognize(&image, &region);
+    assert_eq!(result.glyphs().count(), 1);
+    assert_eq!(result.find_text("一"), Some(Point::new(5, 11)));
+}
+
+#[test]
+fn similarity_tolerates_missing_points() {
+    let dict = dict();
+    let mut image = RgbImage::filled(30, 20, Rgb::WHITE);
+    draw(&mut image, &dict, "口", 2, 2, Rgb::BLACK);
+    // “口”共 12 点，去掉一点
+    image.set(2, 4, Rgb::WHITE);
+    let engine = engine();
+
+    let exact = OcrRequest::new(whole(), Rgb::BLACK).sim(1.0);
+    assert!(engine.recognize(&image, &exact).is_empty());
+    let loose = exact.sim(0.9);
+    assert_eq!(engine.recognize(&image, &loose).text(), "口");
+}
+
+#[test]
+fn colors_text_filter_and_dict_scope() {
+    let dict = dict();
+    let red = Rgb::new(0xFF, 0, 0);
+    let mut image = RgbImage::filled(30, 20, red);
+    draw(&mut image, &dict, "十", 2, 2, Rgb::WHITE);
+    draw(&mut image, &dict, "一", 8, 2, Rgb::WHITE);
+    let mut engine = engine();
+
+    // 以背景色识字时，不是背景色的点都是文字
+    let background = OcrRequest::new(whole(), red)
+        .color_type(OcrColorType::Background)
+        .sim(1.0);
+    assert_eq!(engine.recognize(&image, &background).text(), "十一");
+    let only = background.clone().text("一");
+    assert_eq!(engine.recognize(&image, &only).text(), "一");
+
+    let mut other = Dict::new();
+    other.push(DictEntry::new(
+        "1",
+        dict.get("十").unwrap().bitmap().clone(),
+    ));
+    engine.load(2, other);
+    assert!(!engine.set_dict(5));
+    assert!(engine.set_dict(2));
+    assert_eq!(engine.current(), 2);
+    assert_eq!(engine.recognize(&image, &background).text(), "1");
+    let all = background.dict_scope(OcrDictScope::All);
+    assert_eq!(engine.recognize(&image, &all).text(), "十一");
+}
+
+#[test]
+fn virtual_screen_loads_dicts_from_the_path() {
+    let dir = std::env::temp_dir().join(format!("aojia-ocr-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let dict = dict();
+    dict.save(dir.join("dict.txt")).unwrap();
+
+    let mut frame = RgbImage::filled(30, 20, Rgb::WHITE);
+    draw(&mut frame, &dict, "口", 2, 2, Rgb::BLACK);
+    draw(&mut frame, &dict, "十", 6, 2, Rgb::BLACK);
+    let mut pic = RgbImage::filled(10, 10, Rgb::WHITE);
+    draw(&mut pic, &dict, "一", 1, 1, Rgb::BLACK);
+    let screen = VirtualScreen::new([Frame::new("f", frame)])
+        .unwrap()
+        .pic("word.bmp", pic);
+    let aojia = AoJia::with_backend(&screen);
+
+    aojia.SetPath(dir.to_str().unwrap()).unwrap();
+    aojia.LoadDict(1, "dict.txt").unwrap();
+    aojia.SetDict(1).unwrap();
+    assert!(aojia.LoadDict(2, "nope.txt").is_err());
+
+    let request = OcrRequest::new(whole(), Rgb::BLACK).output(OcrOutput::Positions);
+    assert_eq!(request.run_raw(&aojia).unwrap(), "口,2,2|十,6,2");
+    let in_pic = OcrRequest::new(Rect::new(0, 0, 10, 10), Rgb::BLACK).pic_name("word.bmp");
+    assert_eq!(aojia.ocr(&in_pic).unwrap().text(), "一");
+}
@@ -0,0 +1,137 @@
+use aojia::{
+    AoJia, AoJiaError, MockAoJia, PicMatch, PicResultFormat, PicSearch, Rect, Rgb, SearchDir,
+    Transparency, Value, split_pic_names,
+};
+
+fn region() -> Rect {
+    Rect::new(0, 0, 800, 600)
+}
+
+#[test]
+fn pic_names_are_joined_and_split() {
+    let search = PicSearch::new(region(), ["a.bmp", "b.bmp"]).pic("c.bmp");
+    assert_eq!(search.pic_name(), "a.bmp|b.bmp|c.bmp");
+    assert_eq!(
+        split_pic_names(&search.pic_name()),
+        ["a.bmp", "b.bmp", "c.bmp"]
+    );
+    assert!(split_pic_names("").is_empty());
+    assert!(search.validate().is_ok());
+}
+
+#[test]
+fn builder_fills_find_pic_arguments() {
+    let mock = MockAoJia::new();
+    mock.push_outs(
+        "FindPic",
+        1,
+        [Value::from("b.bmp"), Value::from(3), Value::from(4)],
+    )
+    .push_return("FindPicEx", "b.bmp,3,4|a.bmp,5,6");
+    let aojia = AoJia::with_backend(&mock);
+    let search = PicSearch::new(region(), ["a.bmp", "b.bmp"])
+        .delta(Rgb::new(0x10, 0x20, 0x30))
+        .sim(0.8)
+        .dir(SearchDir::RightBottom)
+        .transparency(Transparency::Opaque)
+        .format(PicResultFormat::Name);
+
+    let found = search.first(&aojia).unwrap().unwrap();
+    assert_eq!((found.index, found.x, found.y), (1, 3, 4));
+    let call = &mock.calls_to("FindPic")[0];
+    assert_eq!(
+        call.inputs(),
+        [
+            &Value::from(0),
+            &Value::from(0),
+            &Value::from(800),
+            &Value::from(600),
+            &Value::from("a.bmp|b.bmp"),
+            &Value::from("102030"),
+            &Value::from(0.8),
+            &Value::from(3),
+            &Value::from(1),
+        ]
+    );
+
+    let all = search.all(&aojia).unwrap();
+    assert_eq!(
+        all,
+        [
+            PicMatch {
+                index: 1,
+                name: "b.bmp".to_string(),
+                x: 3,
+                y: 4
+            },
+            PicMatch {
+                index: 0,
+                name: "a.bmp".to_string(),
+                x: 5,
+                y: 6
+            },
+        ]
+    );
+    assert_eq!(mock.calls_to("FindPicEx")[0].arg(9), &Value::from(1));
+}
+
+#[test]
+fn invalid_names_are_rejected_without_calling_the_plugin() {
+    let mock = MockAoJia::new();
+    let aojia = AoJia::with_backend(&mock);
+    let searches = [
+        PicSearch::new(region(), ["a.bmp|b.bmp"]),
+        PicSearch::new(region(), ["a.bmp", ""]),
+        PicSearch::new(region(), Vec::<String>::new()),
+    ];
+    for search in searches {
+        let reason = search.validate().unwrap_err().reason;
+        match search.first(&aojia).unwrap_err() {
+            AoJiaError::InvalidArgument { call, source } => {
+                assert_eq!(call.name, "FindPic");
+                assert_eq!(call.args.len(), 12);
+                assert_eq!(call.arg(7), &Value::from(search.dir.dir()));
+                assert_eq!(source.reason, reason);
+            }
+            other => panic!("{other}"),
+        }
+        match search.all(&aojia).unwrap_err() {
+            AoJiaError::InvalidArgument { call, .. } => {
+                assert_eq!(call.name, "FindPicEx");
+                assert_eq!(call.args.len(), 10);
+                assert_eq!(call.arg(9), &Value::from(search.format.type_t()));
+            }
+            other => panic!("{other}"),
+        }
+    }
+    assert!(mock.calls().is_empty());
+
+    let err = PicSearch::new(region(), ["ok.bmp", "a|b.bmp"])
+        .validate()
+        .unwrap_err();
+    assert!(err.reason.contains("\"a|b.bmp\""), "{err}");
+}
+
+#[test]
+fn modes_convert_from_plugin_integers() {
+    for dir in [
+        SearchDir::LeftTop,
+        SearchDir::LeftBottom,
+        SearchDir::RightTop,
+        SearchDir::RightBottom,
+    ] {
+        assert_eq!(SearchDir::try_from(dir.dir()).unwrap(), dir);
+    }
+    for ty in [Transparency::Corners, Transparency::Opaque] {
+        assert_eq!(Transparency::try_from(ty.ty()).unwrap(), ty);
+    }
+    for format in [PicResultFormat::Index, PicResultFormat::Name] {
+        assert_eq!(PicResultFormat::try_from(format.type_t()).unwrap(), format);
+    }
+
+    let err = SearchDir::try_from(4).unwrap_err();
+    assert_eq!((err.what, err.input.as_str()), ("Dir", "4"));
+    assert!(err.reason.contains("0、1、2、3"), "{err}");
+    assert!(Transparency::try_from(-1).is_err());
+    assert!(PicResultFormat::try_from(2).is_err());
+}
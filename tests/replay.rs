@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use aojia::{
+    AoJia, AoJiaError, Frame, Journal, JournalEntry, JournalError, MockAoJia, Point, Replay, Rgb,
+    RgbImage, Value, VirtualScreen,
+};
+
+fn journal_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aojia-replay-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// 在模拟后端上执行一段流程并记录日志
+fn record(path: &PathBuf) {
+    let mock = MockAoJia::new();
+    mock.push_return("SetPath", 1).push_return("VerS", "1.2.3");
+    mock.push_outs("GetMousePos", 1, vec![Value::from(7), Value::from(8)]);
+    mock.push_outs(
+        "FindPic",
+        0,
+        vec![Value::from("a.bmp"), Value::from(10), Value::from(20)],
+    );
+    mock.push_error("MoveTo", -5, "窗口已关闭");
+
+    let journal = Journal::open(&mock, path)
+        .unwrap()
+        .redact(|method, _, _| (method == "SetPath").then(|| Value::from("***")));
+    let aojia = AoJia::with_backend(journal);
+    aojia.SetPath("C:/secret").unwrap();
+    aojia.VerS().unwrap();
+    aojia.get_mouse_pos(0).unwrap();
+    aojia
+        .find_pic(0, 0, 800, 600, "a.bmp", Rgb::BLACK, 0.9, 0, 0)
+        .unwrap();
+    aojia.MoveTo(1, 2).unwrap_err();
+    assert!(aojia.backend().take_error().is_none());
+}
+
+#[test]
+fn replays_recorded_results() {
+    let path = journal_path("results.jsonl");
+    record(&path);
+
+    let replay = Replay::open(&path).unwrap().placeholder("***");
+    let aojia = AoJia::with_backend(&replay);
+    aojia.SetPath("D:/other").unwrap();
+    assert_eq!(aojia.VerS().unwrap(), "1.2.3");
+    assert_eq!(aojia.get_mouse_pos(0).unwrap(), Point::new(7, 8));
+    let found = aojia
+        .find_pic(0, 0, 800, 600, "a.bmp", Rgb::BLACK, 0.9, 0, 0)
+        .unwrap()
+        .unwrap();
+    assert_eq!((found.name.as_str(), found.x, found.y), ("a.bmp", 10, 20));
+    let err = aojia.MoveTo(1, 2).unwrap_err();
+    assert_eq!(err.hresult(), Some(-5));
+    replay.finish().unwrap();
+}
+
+#[test]
+fn replays_errors_with_their_original_kind() {
+    let path = journal_path("kinds.jsonl");
+    let screen = VirtualScreen::new([Frame::new("f", RgbImage::filled(8, 8, Rgb::WHITE))]).unwrap();
+    let journal = Journal::open(&screen, &path).unwrap();
+    let aojia = AoJia::with_backend(journal);
+    // 虚拟屏幕不认识的查找方向
+    let recorded = aojia
+        .FindPicEx(0, 0, 8, 8, "a.bmp", Rgb::BLACK, 0.9, 4, 0, 0)
+        .unwrap_err();
+    assert!(matches!(recorded, AoJiaError::InvalidArgument { .. }));
+
+    let replay = Replay::open(&path).unwrap();
+    let aojia = AoJia::with_backend(&replay);
+    let replayed = aojia
+        .FindPicEx(0, 0, 8, 8, "a.bmp", Rgb::BLACK, 0.9, 4, 0, 0)
+        .unwrap_err();
+    assert_eq!(replayed, recorded);
+    replay.finish().unwrap();
+}
+
+#[test]
+fn every_error_kind_is_rebuilt() {
+    let entry = |kind: Option<JournalError>| JournalEntry {
+        ts: 0,
+        method: "VerS".to_string(),
+        args: vec![],
+        outs: vec![],
+        ret: Value::Empty,
+        hresult: -3,
+        error: Some("出错".to_string()),
+        kind,
+        duration_us: 0,
+    };
+    let kinds = [
+        Some(JournalError::Com),
+        None,
+        Some(JournalError::Conversion {
+            value: "I32(1)".to_string(),
+            target: "String".to_string(),
+        }),
+        Some(JournalError::Parse {
+            what: "VerS 结果".to_string(),
+            input: "x".to_string(),
+            reason: "不是版本号".to_string(),
+        }),
+        Some(JournalError::Plugin { code: 0 }),
+        Some(JournalError::InvalidArgument {
+            what: "绑定标志".to_string(),
+            input: "y".to_string(),
+            reason: "未知标志".to_string(),
+        }),
+        Some(JournalError::Timeout { timeout_us: 1500 }),
+        Some(JournalError::Replay {
+            reason: "上次重放失败".to_string(),
+        }),
+    ];
+    // 经过 JSON 往返后再重放
+    let text: String = kinds
+        .iter()
+        .map(|kind| serde_json::to_string(&entry(kind.clone())).unwrap() + "\n")
+        .collect();
+    let replay = Replay::parse(&text).unwrap();
+    let aojia = AoJia::with_backend(&replay);
+    let errors: Vec<AoJiaError> = kinds.iter().map(|_| aojia.VerS().unwrap_err()).collect();
+    replay.finish().unwrap();
+
+    assert_eq!(errors[0].hresult(), Some(-3));
+    // 旧日志没有错误种类，按 COM 错误重放
+    assert_eq!(errors[1], errors[0]);
+    let AoJiaError::Conversion { source, .. } = &errors[2] else {
+        panic!("{}", errors[2]);
+    };
+    assert_eq!((source.value.as_str(), source.target), ("I32(1)", "String"));
+    let AoJiaError::Parse { source, .. } = &errors[3] else {
+        panic!("{}", errors[3]);
+    };
+    assert_eq!((source.what, source.input.as_str()), ("VerS 结果", "x"));
+    assert!(matches!(errors[4], AoJiaError::Plugin { code: 0, .. }));
+    let AoJiaError::InvalidArgument { source, .. } = &errors[5] else {
+        panic!("{}", errors[5]);
+    };
+    assert_eq!(source.reason, "未知标志");
+    let AoJiaError::Timeout { timeout, .. } = &errors[6] else {
+        panic!("{}", errors[6]);
+    };
+    assert_eq!(*timeout, Duration::from_micros(1500));
+    assert!(matches!(&errors[7], AoJiaError::Replay { reason, .. } if reason == "上次重放失败"));
+    for error in &errors[2..] {
+        assert_eq!(error.hresult(), None, "{error}");
+    }
+}
+
+#[test]
+fn first_divergence_poisons_the_replay() {
+    let path = journal_path("divergence.jsonl");
+    record(&path);
+
+    let replay = Replay::open(&path).unwrap().placeholder("***");
+    let aojia = AoJia::with_backend(&replay);
+    aojia.SetPath("C:/secret").unwrap();
+    aojia.VerS().unwrap();
+
+    let err = aojia.MoveTo(1, 2).unwrap_err();
+    let AoJiaError::Replay { reason, .. } = &err else {
+        panic!("{err}");
+    };
+    assert!(reason.contains("GetMousePos"), "{reason}");
+
+    // 此后即使调用与日志一致也会失败
+    assert!(matches!(
+        aojia.get_mouse_pos(0),
+        Err(AoJiaError::Replay { .. })
+    ));
+    assert!(replay.finish().is_err());
+}
+
+#[test]
+fn argument_mismatch_is_reported() {
+    let path = journal_path("arguments.jsonl");
+    record(&path);
+
+    let replay = Replay::open(&path).unwrap();
+    let aojia = AoJia::with_backend(&replay);
+    // 未设置占位值时，遮盖过的参数不再匹配
+    let err = aojia.SetPath("C:/secret").unwrap_err();
+    assert!(err.to_string().contains("\"***\""), "{err}");
+}
+
+#[test]
+fn malformed_lines_are_rejected() {
+    let err = Replay::parse("{\"method\":1}\n").unwrap_err();
+    assert!(err.to_string().starts_with("第 1 行"), "{err}");
+}
@@ -0,0 +1,167 @@
+use aojia::spec::{Direction, Spec};
+use aojia::{AoJia, AoJiaError, Arg, BINDINGS, Hwnd, MockAoJia, Rgb, Value};
+
+fn normalize(ty: &str) -> String {
+    ty.split_whitespace().collect()
+}
+
+#[test]
+fn every_binding_matches_its_spec_entry() {
+    let spec = Spec::builtin();
+    for b in BINDINGS {
+        let m = spec
+            .method(b.name)
+            .unwrap_or_else(|| panic!("{} 不在 spec/aojia.json 中", b.name));
+        assert_eq!(
+            normalize(b.returns),
+            m.returns.rust_owned(),
+            "{} 的返回值",
+            b.name
+        );
+        assert_eq!(b.failure, m.failure.as_slice(), "{} 的失败码", b.name);
+        assert_eq!(b.params.len(), m.params.len(), "{} 的参数个数", b.name);
+        for (bp, sp) in b.params.iter().zip(&m.params) {
+            assert_eq!(bp.name, sp.name, "{} 的参数顺序", b.name);
+            assert_eq!(
+                bp.out,
+                sp.dir == Direction::Out,
+                "{}.{} 的方向",
+                b.name,
+                sp.name
+            );
+            let expected = match sp.dir {
+                Direction::In => sp.ty.rust_in(),
+                Direction::Out => sp.ty.rust_owned(),
+            };
+            assert_eq!(normalize(bp.ty), expected, "{}.{} 的类型", b.name, sp.name);
+        }
+    }
+}
+
+#[test]
+fn every_spec_entry_is_bound() {
+    for m in Spec::builtin().methods {
+        assert!(
+            BINDINGS.iter().any(|b| b.name == m.name),
+            "{} 没有生成包装方法",
+            m.name
+        );
+    }
+}
+
+#[test]
+fn spec_names_are_unique() {
+    let spec = Spec::builtin();
+    for (i, m) in spec.methods.iter().enumerate() {
+        assert!(
+            spec.methods[i + 1..].iter().all(|n| n.name != m.name),
+            "{} 重复",
+            m.name
+        );
+        for (j, p) in m.params.iter().enumerate() {
+            assert!(
+                m.params[j + 1..].iter().all(|q| q.name != p.name),
+                "{}.{} 重复",
+                m.name,
+                p.name
+            );
+        }
+    }
+}
+
+#[test]
+fn wrappers_pass_arguments_in_declared_order() {
+    let mock = MockAoJia::new();
+    mock.push_return("FindWindow", 0x1234);
+    let aojia = AoJia::with_backend(&mock);
+    let hwnd = aojia
+        .FindWindow(Hwnd::new(7), "game.exe", 42, "", "标题", 1, 500)
+        .unwrap();
+    assert_eq!(hwnd, Hwnd::new(0x1234));
+
+    let call = &mock.calls()[0];
+    assert_eq!(call.name, "FindWindow");
+    // 空字符串按 VT_EMPTY 传递
+    assert_eq!(
+        call.args,
+        [
+            Arg::input(7),
+            Arg::input("game.exe"),
+            Arg::input(42),
+            Arg::In(Value::Empty),
+            Arg::input("标题"),
+            Arg::input(1),
+            Arg::input(500),
+        ]
+    );
+}
+
+#[test]
+fn wrappers_write_back_out_params() {
+    let mock = MockAoJia::new();
+    mock.push_outs(
+        "FindPic",
+        0,
+        [Value::from("b.bmp"), Value::from(10), Value::from("20")],
+    );
+    let aojia = AoJia::with_backend(&mock);
+    let (mut pic, mut x, mut y) = (String::new(), -1, -1);
+    let index = aojia
+        .FindPic(
+            0,
+            0,
+            800,
+            600,
+            "a.bmp|b.bmp",
+            Rgb::new(0x10, 0x20, 0x30),
+            0.9,
+            0,
+            1,
+            &mut pic,
+            &mut x,
+            &mut y,
+        )
+        .unwrap();
+    assert_eq!((index, pic.as_str(), x, y), (0, "b.bmp", 10, 20));
+
+    // 传址参数排在传值参数之后，调用时为空
+    let call = &mock.calls()[0];
+    assert_eq!(call.args.len(), 12);
+    assert_eq!(call.arg(5), &Value::from("102030"));
+    assert!(call.args[9..].iter().all(|a| *a == Arg::out()));
+}
+
+#[test]
+fn failure_codes_become_plugin_errors() {
+    let mock = MockAoJia::new();
+    mock.push_return("SetPath", 1)
+        .push_return("SetPath", 0)
+        .push_return("GetClientSize", "abc");
+    let aojia = AoJia::with_backend(&mock);
+
+    assert_eq!(aojia.SetPath("C:/pics").unwrap(), 1);
+    let err = aojia.SetPath("C:/nope").unwrap_err();
+    assert!(matches!(err, AoJiaError::Plugin { code: 0, .. }), "{err}");
+    assert_eq!(err.call().unwrap().arg(0), &Value::from("C:/nope"));
+
+    let (mut w, mut h) = (0, 0);
+    let err = aojia
+        .GetClientSize(Hwnd::new(1), &mut w, &mut h)
+        .unwrap_err();
+    assert!(matches!(err, AoJiaError::Conversion { .. }), "{err}");
+}
+
+#[test]
+fn resolve_all_reports_exactly_the_missing_methods() {
+    let mock = MockAoJia::new();
+    let aojia = AoJia::with_backend(&mock);
+    assert!(aojia.resolve_all().is_empty());
+
+    mock.set_missing("KQHouTai")
+        .set_missing("Ocr")
+        .set_missing("NotABinding");
+    assert_eq!(aojia.resolve_all(), ["KQHouTai", "Ocr"]);
+    assert!(aojia.resolve("Ocr").is_err());
+    // 解析不会调用插件方法
+    assert!(mock.calls().is_empty());
+}
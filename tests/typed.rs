@@ -0,0 +1,155 @@
+use aojia::{
+    AoJia, AoJiaError, CpuInfo, Hwnd, MockAoJia, OsInfo, PicMatch, Point, Rgb, Size, Value,
+};
+
+fn aojia(mock: &MockAoJia) -> AoJia<&MockAoJia> {
+    AoJia::with_backend(mock)
+}
+
+#[test]
+fn find_pic_returns_the_match_or_none() {
+    let mock = MockAoJia::new();
+    mock.push_outs(
+        "FindPic",
+        1,
+        [Value::from("b.bmp"), Value::from(10), Value::from(20)],
+    )
+    .push_outs(
+        "FindPic",
+        -1,
+        [Value::from(""), Value::from(-1), Value::from(-1)],
+    );
+    let aojia = aojia(&mock);
+
+    let found = aojia
+        .find_pic(0, 0, 800, 600, "a.bmp|b.bmp", Rgb::BLACK, 0.9, 0, 0)
+        .unwrap();
+    assert_eq!(
+        found,
+        Some(PicMatch {
+            index: 1,
+            name: "b.bmp".to_string(),
+            x: 10,
+            y: 20
+        })
+    );
+    assert_eq!(found.unwrap().point(), Point::new(10, 20));
+    let missing = aojia
+        .find_pic(0, 0, 800, 600, "a.bmp", Rgb::BLACK, 0.9, 0, 0)
+        .unwrap();
+    assert_eq!(missing, None);
+}
+
+#[test]
+fn system_info_is_collected_from_out_params() {
+    let mock = MockAoJia::new();
+    mock.push_outs(
+        "GetOs",
+        1,
+        [
+            Value::from("10.0"),
+            Value::from("Windows 10"),
+            Value::from(19045),
+            Value::from("C:\\Windows\\system32"),
+        ],
+    )
+    .push_outs("GetCPU", 1, ["Intel", "BFEBFBFF000906EA"]);
+    let aojia = aojia(&mock);
+
+    assert_eq!(
+        aojia.get_os(0).unwrap(),
+        OsInfo {
+            version: "10.0".to_string(),
+            version_name: "Windows 10".to_string(),
+            build: 19045,
+            system_dir: "C:\\Windows\\system32".to_string(),
+        }
+    );
+    assert_eq!(
+        aojia.get_cpu().unwrap(),
+        CpuInfo {
+            model: "Intel".to_string(),
+            id: "BFEBFBFF000906EA".to_string(),
+        }
+    );
+}
+
+#[test]
+fn sizes_and_points_are_collected_from_out_params() {
+    let mock = MockAoJia::new();
+    mock.push_outs("GetClientSize", 1, [800, 600])
+        .push_outs("GetWindowSize", 1, [816, 639])
+        .push_outs("ClientToScreen", 1, [108, 231])
+        .push_outs("ClientOrScreen", 1, [5, 6])
+        .push_outs("GetMousePos", 1, [7, 8]);
+    let aojia = aojia(&mock);
+    let hwnd = Hwnd::new(0x1234);
+
+    assert_eq!(
+        aojia.get_client_size(hwnd).unwrap(),
+        Size {
+            width: 800,
+            height: 600
+        }
+    );
+    assert_eq!(
+        aojia.get_window_size(hwnd).unwrap(),
+        Size {
+            width: 816,
+            height: 639
+        }
+    );
+    assert_eq!(aojia.client_to_screen(hwnd).unwrap(), Point::new(108, 231));
+    assert_eq!(
+        aojia
+            .client_or_screen(hwnd, Point::new(100, 200), 1)
+            .unwrap(),
+        Point::new(5, 6)
+    );
+    assert_eq!(aojia.get_mouse_pos(0).unwrap(), Point::new(7, 8));
+
+    let call = &mock.calls_to("ClientOrScreen")[0];
+    assert_eq!(
+        call.inputs(),
+        [
+            &Value::from(0x1234),
+            &Value::from(100),
+            &Value::from(200),
+            &Value::from(1)
+        ]
+    );
+}
+
+#[test]
+fn unwritten_out_params_are_conversion_errors() {
+    let mock = MockAoJia::new();
+    // 只写回了 x
+    mock.push_outs("GetMousePos", 1, [7])
+        .push_return("GetClientSize", 1)
+        .push_outs("GetCPU", 1, [Value::Empty, Value::Empty]);
+    let aojia = aojia(&mock);
+
+    let err = aojia.get_mouse_pos(0).unwrap_err();
+    match &err {
+        AoJiaError::Conversion { call, source } => {
+            assert_eq!(call.name, "GetMousePos");
+            assert_eq!(source.target, "i32");
+        }
+        _ => panic!("{err}"),
+    }
+    assert!(matches!(
+        aojia.get_client_size(Hwnd::NULL).unwrap_err(),
+        AoJiaError::Conversion { .. }
+    ));
+    // 字符串为空即为空字符串
+    assert_eq!(aojia.get_cpu().unwrap(), CpuInfo::default());
+}
+
+#[test]
+fn unparsable_values_are_conversion_errors() {
+    let mock = MockAoJia::new();
+    mock.push_outs("GetMousePos", 1, [Value::from("12"), Value::from("abc")]);
+    let err = aojia(&mock).get_mouse_pos(0).unwrap_err();
+    assert!(matches!(err, AoJiaError::Conversion { .. }), "{err}");
+    assert!(err.to_string().contains("\"abc\""), "{err}");
+}
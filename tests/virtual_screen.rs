@@ -0,0 +1,266 @@
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, Weak};
+
+use aojia::{
+    Advance, AoJia, AoJiaError, ColorSpec, Frame, Glyph, OcrLine, OcrOutput, OcrRequest, OcrResult,
+    PicSearch, Point, Rect, Rgb, RgbImage, Size, Value, VirtualScreen,
+};
+
+const RED: Rgb = Rgb::new(0xFF, 0, 0);
+const BLUE: Rgb = Rgb::new(0, 0, 0xFF);
+
+fn button(color: Rgb) -> RgbImage {
+    RgbImage::from_fn(4, 3, |x, _| if x == 0 { Rgb::BLACK } else { color })
+}
+
+fn frame_with(pic: &RgbImage, x: i32, y: i32) -> RgbImage {
+    let mut image = RgbImage::filled(40, 30, Rgb::WHITE);
+    image.draw(pic, x, y);
+    image
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aojia-screen-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn script_runs_against_recorded_frames() {
+    let dir = temp_dir("script");
+    frame_with(&button(RED), 10, 5)
+        .save_bmp(dir.join("001.bmp"))
+        .unwrap();
+    frame_with(&button(BLUE), 20, 15)
+        .save_bmp(dir.join("002.bmp"))
+        .unwrap();
+    std::fs::write(dir.join("notes.txt"), "不是截图").unwrap();
+    let assets = dir.join("assets");
+    std::fs::create_dir(&assets).unwrap();
+    button(RED).save_bmp(assets.join("login.bmp")).unwrap();
+
+    let screen = VirtualScreen::open_dir(&dir)
+        .unwrap()
+        .pic("done.bmp", button(BLUE))
+        .advance_on(Advance::After("LeftClick".to_string()));
+    assert_eq!(screen.frames().len(), 2);
+    assert_eq!(screen.frame().name, "001.bmp");
+
+    let aojia = AoJia::with_backend(&screen);
+    aojia.SetPath(assets.to_str().unwrap()).unwrap();
+    let search = PicSearch::new(screen.frame().image.rect(), ["login.bmp", "done.bmp"]);
+    let found = search.first(&aojia).unwrap().unwrap();
+    assert_eq!(
+        (found.name.as_str(), found.point()),
+        ("login.bmp", Point::new(10, 5))
+    );
+
+    aojia.MoveTo(found.x + 1, found.y + 1).unwrap();
+    aojia.LeftClick().unwrap();
+    assert_eq!(screen.frame_index(), 1);
+
+    let found = search.all(&aojia).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].index, found[0].point()), (1, Point::new(20, 15)));
+    assert_eq!(aojia.get_mouse_pos(0).unwrap(), Point::new(11, 6));
+
+    let inputs: Vec<_> = screen
+        .inputs()
+        .into_iter()
+        .map(|e| (e.frame, e.call.name))
+        .collect();
+    assert_eq!(
+        inputs,
+        [(0, "MoveTo".to_string()), (0, "LeftClick".to_string())]
+    );
+}
+
+#[test]
+fn frames_advance_on_inputs_and_polling() {
+    let frames = (0..3).map(|i| Frame::new(format!("{i}"), RgbImage::filled(8, 8, Rgb::WHITE)));
+    let screen = VirtualScreen::new(frames)
+        .unwrap()
+        .pic("a.bmp", button(RED))
+        .advance_on(Advance::EveryInput)
+        .advance_on(Advance::ScreenReads(2));
+    let aojia = AoJia::with_backend(&screen);
+    let search = PicSearch::new(Rect::new(0, 0, 8, 8), ["a.bmp"]);
+
+    assert!(search.first(&aojia).unwrap().is_none());
+    assert_eq!(screen.frame_index(), 0);
+    assert!(search.first(&aojia).unwrap().is_none());
+    assert_eq!(screen.frame_index(), 1);
+    aojia.LeftClick().unwrap();
+    assert_eq!(screen.frame_index(), 2);
+    // 停在最后一帧
+    aojia.LeftClick().unwrap();
+    assert_eq!(screen.frame_index(), 2);
+    assert!(!screen.next_frame());
+
+    screen.set_frame(0);
+    assert_eq!(screen.frame_index(), 0);
+    assert_eq!(
+        aojia.get_client_size(aojia::Hwnd::NULL).unwrap(),
+        Size {
+            width: 8,
+            height: 8
+        }
+    );
+}
+
+#[test]
+fn ocr_is_served_by_the_hook() {
+    let screen = VirtualScreen::new([Frame::new("f", RgbImage::filled(8, 8, Rgb::WHITE))])
+        .unwrap()
+        .ocr_with(|image, request| {
+            assert_eq!(image.width(), 8);
+            assert_eq!(request.region, Rect::new(0, 0, 8, 8));
+            OcrResult {
+                lines: vec![OcrLine::from_glyphs(vec![
+                    Glyph {
+                        ch: "确".to_string(),
+                        x: 1,
+                        y: 2,
+                    },
+                    Glyph {
+                        ch: "定".to_string(),
+                        x: 5,
+                        y: 2,
+                    },
+                ])],
+            }
+        });
+    let aojia = AoJia::with_backend(&screen);
+    let request = OcrRequest::new(Rect::new(0, 0, 8, 8), Rgb::BLACK);
+    assert_eq!(aojia.ocr(&request).unwrap().text(), "确定");
+    let positions = request.output(OcrOutput::Positions);
+    assert_eq!(positions.run_raw(&aojia).unwrap(), "确,1,2|定,5,2");
+    assert_eq!(
+        aojia.ocr(&positions).unwrap().find_text("定"),
+        Some(Point::new(5, 2))
+    );
+}
+
+#[test]
+fn missing_pictures_and_hooks_are_errors() {
+    let screen = VirtualScreen::new([Frame::new("f", RgbImage::filled(8, 8, Rgb::WHITE))])
+        .unwrap()
+        .pic_dir(temp_dir("missing"))
+        .handle("GetColor", |image, args| {
+            let x = args[0].value().to_i32().unwrap() as u32;
+            let y = args[1].value().to_i32().unwrap() as u32;
+            Ok(Value::from(image.pixel(x, y).to_string()))
+        });
+    let aojia = AoJia::with_backend(&screen);
+
+    let err = PicSearch::new(Rect::new(0, 0, 8, 8), ["nope.bmp"])
+        .first(&aojia)
+        .unwrap_err();
+    assert!(err.to_string().contains("nope.bmp"), "{err}");
+
+    let err = aojia
+        .ocr(&OcrRequest::new(Rect::new(0, 0, 8, 8), Rgb::BLACK))
+        .unwrap_err();
+    assert!(matches!(err, AoJiaError::Com { .. }), "{err}");
+
+    let mut args = [aojia::Arg::input(3), aojia::Arg::input(4)];
+    let color = aojia::Dispatch::invoke(&screen, "GetColor", &mut args).unwrap();
+    assert_eq!(color, Value::from("FFFFFF"));
+}
+
+#[test]
+fn unknown_modes_are_invalid_arguments() {
+    let screen = VirtualScreen::new([Frame::new("f", RgbImage::filled(8, 8, Rgb::WHITE))])
+        .unwrap()
+        .pic("a.bmp", button(RED))
+        .ocr_with(|_, _| OcrResult::default());
+    let aojia = AoJia::with_backend(&screen);
+    let invalid_arg = |err: AoJiaError, param: &str| match err {
+        AoJiaError::InvalidArgument { source, .. } => assert_eq!(source.what, param),
+        err => panic!("{err}"),
+    };
+
+    // 依次为 Dir、Type、TypeT
+    for (dir, ty, type_t, param) in [(4, 0, 0, "Dir"), (0, 2, 0, "Type"), (0, 0, -1, "TypeT")] {
+        let err = aojia
+            .FindPicEx(0, 0, 8, 8, "a.bmp", Rgb::BLACK, 0.9, dir, ty, type_t)
+            .unwrap_err();
+        invalid_arg(err, param);
+    }
+    assert_eq!(
+        aojia
+            .FindPicEx(0, 0, 8, 8, "a.bmp", Rgb::BLACK, 0.9, 3, 1, 1)
+            .unwrap(),
+        ""
+    );
+
+    let color = ColorSpec::new(Rgb::BLACK);
+    let modes = [[2, 0, 0, 0], [0, 2, 0, 0], [0, 0, 2, 0], [0, 0, 0, 2]];
+    for (modes, param) in modes.into_iter().zip(["TypeC", "TypeD", "TypeR", "TypeT"]) {
+        let [c, d, r, t] = modes;
+        let err = aojia
+            .Ocr(0, 0, 8, 8, "", &color, 0.9, c, d, r, t, "", "")
+            .unwrap_err();
+        invalid_arg(err, param);
+    }
+    assert_eq!(
+        aojia
+            .Ocr(0, 0, 8, 8, "", &color, 0.9, 1, 1, 1, 1, "", "")
+            .unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn empty_frame_lists_are_errors() {
+    assert!(VirtualScreen::new([]).is_err());
+    assert!(VirtualScreen::open(Vec::<PathBuf>::new()).is_err());
+    let dir = temp_dir("empty");
+    std::fs::write(dir.join("notes.txt"), "不是截图").unwrap();
+    assert!(VirtualScreen::open_dir(&dir).is_err());
+}
+
+#[test]
+fn hooks_can_call_back_into_the_screen() {
+    let cell: Arc<OnceLock<Weak<VirtualScreen>>> = Arc::default();
+    let screen = |cell: &OnceLock<Weak<VirtualScreen>>| cell.get().unwrap().upgrade().unwrap();
+    let frames = (0..2).map(|i| Frame::new(format!("{i}"), RgbImage::filled(8, 8, Rgb::WHITE)));
+    let (handler_cell, ocr_cell) = (cell.clone(), cell.clone());
+    let shared = Arc::new(
+        VirtualScreen::new(frames)
+            .unwrap()
+            .handle("MoveToCenter", move |image, _| {
+                let screen = screen(&handler_cell);
+                let (x, y) = (image.width() as i32 / 2, image.height() as i32 / 2);
+                AoJia::with_backend(&*screen).MoveTo(x, y)?;
+                Ok(Value::from(1))
+            })
+            .ocr_with(move |_, _| {
+                let screen = screen(&ocr_cell);
+                screen.next_frame();
+                OcrResult::default()
+            }),
+    );
+    cell.set(Arc::downgrade(&shared)).unwrap();
+    let aojia = AoJia::with_backend(&*shared);
+
+    let mut args = [];
+    aojia::Dispatch::invoke(&*shared, "MoveToCenter", &mut args).unwrap();
+    assert_eq!(shared.mouse(), Point::new(4, 4));
+    assert_eq!(shared.inputs().len(), 1);
+    aojia
+        .ocr(&OcrRequest::new(Rect::new(0, 0, 8, 8), Rgb::BLACK))
+        .unwrap();
+    assert_eq!(shared.frame_index(), 1);
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn png_frames_are_loaded() {
+    let dir = temp_dir("png");
+    let image = frame_with(&button(RED), 3, 4);
+    image.save_png(dir.join("a.png")).unwrap();
+    let screen = VirtualScreen::open_dir(&dir).unwrap();
+    assert_eq!(screen.frame().image, image);
+}
@@ -0,0 +1,141 @@
+use std::thread;
+use std::time::Duration;
+
+use aojia::{
+    AoJia, AoJiaError, CancelToken, Hwnd, MockAoJia, OcrRequest, PicSearch, Point, Rect, Reply,
+    Rgb, Value, Wait,
+};
+
+fn quick() -> Wait {
+    Wait::new(Duration::from_secs(5)).interval(Duration::from_millis(1))
+}
+
+fn not_found() -> Reply {
+    Reply::with_outs(-1, [Value::from(""), Value::from(-1), Value::from(-1)])
+}
+
+fn search() -> PicSearch {
+    PicSearch::new(Rect::new(0, 0, 800, 600), ["ok.bmp"])
+}
+
+#[test]
+fn until_returns_after_the_nth_poll() {
+    let mut polls = 0;
+    let found = quick()
+        .until("计数", || {
+            polls += 1;
+            Ok((polls == 3).then_some(polls))
+        })
+        .unwrap();
+    assert_eq!(found, 3);
+}
+
+#[test]
+fn wait_for_pic_polls_until_found() {
+    let mock = MockAoJia::new();
+    mock.push("FindPic", not_found())
+        .push("FindPic", not_found())
+        .push_outs(
+            "FindPic",
+            0,
+            [Value::from("ok.bmp"), Value::from(5), Value::from(6)],
+        );
+    let aojia = AoJia::with_backend(&mock);
+
+    let found = aojia.wait_for_pic(&search(), &quick()).unwrap();
+    assert_eq!(found.point(), Point::new(5, 6));
+    assert_eq!(mock.calls_to("FindPic").len(), 3);
+
+    mock.push_outs(
+        "FindPic",
+        0,
+        [Value::from("ok.bmp"), Value::from(5), Value::from(6)],
+    )
+    .set_default("FindPic", not_found());
+    aojia.wait_for_pic_gone(&search(), &quick()).unwrap();
+    assert_eq!(mock.calls_to("FindPic").len(), 5);
+}
+
+#[test]
+fn timeouts_report_the_poll_count() {
+    let mock = MockAoJia::new();
+    mock.set_default("FindWindow", Reply::ret(0));
+    let aojia = AoJia::with_backend(&mock);
+    let wait = Wait::new(Duration::from_millis(30)).interval(Duration::from_millis(5));
+
+    let err = aojia.wait_for_window("Game", "", &wait).unwrap_err();
+    match err {
+        AoJiaError::WaitTimeout {
+            what,
+            polls,
+            elapsed,
+        } => {
+            assert!(what.contains("\"Game\""), "{what}");
+            assert!(polls >= 2, "{polls}");
+            assert_eq!(polls as usize, mock.calls_to("FindWindow").len());
+            assert!(elapsed >= Duration::from_millis(30));
+        }
+        other => panic!("{other}"),
+    }
+
+    // 超时为 0 时仍检查一次
+    mock.push_return("FindWindow", 0x42);
+    let hwnd = aojia
+        .wait_for_window("Game", "", &Wait::new(Duration::ZERO))
+        .unwrap();
+    assert_eq!(hwnd, Hwnd::new(0x42));
+}
+
+#[test]
+fn cancellation_stops_the_wait() {
+    let mock = MockAoJia::new();
+    mock.set_default("FindPic", not_found());
+    let aojia = AoJia::with_backend(&mock);
+
+    let token = CancelToken::new();
+    token.cancel();
+    let err = aojia
+        .wait_for_pic(&search(), &quick().cancel(token))
+        .unwrap_err();
+    assert!(
+        matches!(err, AoJiaError::Cancelled { polls: 0, .. }),
+        "{err}"
+    );
+    assert!(mock.calls().is_empty());
+
+    // 在其他线程取消正在休眠的等待
+    let token = CancelToken::new();
+    let wait = Wait::new(Duration::from_secs(60))
+        .interval(Duration::from_secs(10))
+        .cancel(token.clone());
+    let err = thread::scope(|s| {
+        let waiting = s.spawn(|| aojia.wait_for_pic(&search(), &wait));
+        while mock.calls().is_empty() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        token.cancel();
+        waiting.join().unwrap().unwrap_err()
+    });
+    assert!(
+        matches!(err, AoJiaError::Cancelled { polls: 1, .. }),
+        "{err}"
+    );
+}
+
+#[test]
+fn poll_errors_are_returned_without_retrying() {
+    let mock = MockAoJia::new();
+    mock.push_error("Ocr", -3, "字库未加载")
+        .set_default("Ocr", Reply::ret("确,1,2"));
+    let aojia = AoJia::with_backend(&mock);
+    let request = OcrRequest::new(Rect::new(0, 0, 100, 100), Rgb::WHITE);
+
+    let err = aojia.wait_for_text(&request, "确", &quick()).unwrap_err();
+    assert_eq!(err.hresult(), Some(-3));
+    assert_eq!(mock.calls_to("Ocr").len(), 1);
+
+    // 之后的等待使用坐标输出
+    let point = aojia.wait_for_text(&request, "确", &quick()).unwrap();
+    assert_eq!(point, Point::new(1, 2));
+    assert_eq!(mock.calls_to("Ocr")[1].arg(9), &Value::from(1));
+}
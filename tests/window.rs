@@ -0,0 +1,52 @@
+use aojia::{AoJia, AoJiaError, Hwnd, MockAoJia, parse_enum_window};
+
+fn hwnds(raw: &[i32]) -> Vec<Hwnd> {
+    raw.iter().copied().map(Hwnd::new).collect()
+}
+
+#[test]
+fn handle_lists_use_either_separator() {
+    assert_eq!(parse_enum_window("").unwrap(), []);
+    assert_eq!(parse_enum_window("  ").unwrap(), []);
+    assert_eq!(parse_enum_window("1234").unwrap(), hwnds(&[1234]));
+    assert_eq!(
+        parse_enum_window("1|22,333 | 4444\r\n").unwrap(),
+        hwnds(&[1, 22, 333, 4444])
+    );
+}
+
+#[test]
+fn empty_items_and_garbage_are_rejected() {
+    for raw in ["1||2", "1,2,", "|1", ",", "1| |2"] {
+        let err = parse_enum_window(raw).unwrap_err();
+        assert!(err.reason.contains("为空"), "{raw:?}: {err}");
+    }
+    let err = parse_enum_window("1|abc").unwrap_err();
+    assert!(err.reason.contains("\"abc\""), "{err}");
+    assert_eq!(err.input, "1|abc");
+}
+
+#[test]
+fn enum_window_reports_the_call_on_parse_errors() {
+    let mock = MockAoJia::new();
+    mock.push_return("EnumWindow", "100|200")
+        .push_return("EnumWindow", "100||200");
+    let aojia = AoJia::with_backend(&mock);
+
+    let found = aojia
+        .enum_window(Hwnd::NULL, "game.exe", 0, "", "", 1, 0, 0)
+        .unwrap();
+    assert_eq!(found, hwnds(&[100, 200]));
+
+    let err = aojia
+        .enum_window(Hwnd::NULL, "game.exe", 0, "", "", 1, 0, 0)
+        .unwrap_err();
+    match err {
+        AoJiaError::Parse { call, source } => {
+            // 与实际发出的调用一致
+            assert_eq!(call, mock.calls()[1]);
+            assert_eq!(source.input, "100||200");
+        }
+        other => panic!("{other}"),
+    }
+}